@@ -0,0 +1,60 @@
+//! Authentication support.
+
+use crate::message::backend::AuthenticationSha256PasswordBody;
+
+pub mod sasl;
+pub mod sha256;
+pub mod sm3;
+
+/// The digest openGauss's `rfc5802_algorithm` authentication should use, as selected by the
+/// server's `password_encryption_type` GUC and reported in its authentication request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rfc5802Digest {
+    /// `password_encryption_type` is the default, SHA256-based setting.
+    Sha256,
+    /// `password_encryption_type` is configured to hash credentials with SM3.
+    Sm3,
+}
+
+/// Runs `rfc5802_algorithm` with whichever digest `digest` selects.
+pub fn rfc5802_algorithm(
+    digest: Rfc5802Digest,
+    password: &[u8],
+    body: AuthenticationSha256PasswordBody,
+) -> Vec<u8> {
+    match digest {
+        Rfc5802Digest::Sha256 => sha256::rfc5802_algorithm(password, body),
+        Rfc5802Digest::Sm3 => sm3::rfc5802_algorithm(password, body),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn body() -> AuthenticationSha256PasswordBody {
+        AuthenticationSha256PasswordBody::new([49; 64], [51; 8], [0, 0, 0, 1])
+    }
+
+    #[test]
+    fn dispatches_to_the_matching_digest_implementation() {
+        let password = b"hunter2";
+        assert_eq!(
+            rfc5802_algorithm(Rfc5802Digest::Sha256, password, body()),
+            sha256::rfc5802_algorithm(password, body())
+        );
+        assert_eq!(
+            rfc5802_algorithm(Rfc5802Digest::Sm3, password, body()),
+            sm3::rfc5802_algorithm(password, body())
+        );
+    }
+
+    #[test]
+    fn sha256_and_sm3_produce_different_responses() {
+        let password = b"hunter2";
+        assert_ne!(
+            rfc5802_algorithm(Rfc5802Digest::Sha256, password, body()),
+            rfc5802_algorithm(Rfc5802Digest::Sm3, password, body())
+        );
+    }
+}
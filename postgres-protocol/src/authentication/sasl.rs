@@ -0,0 +1,330 @@
+//! SASL/SCRAM-SHA-256 authentication support.
+//!
+//! Complements [`super::sha256`]'s openGauss-specific `rfc5802_algorithm`: this module implements
+//! the standard SCRAM-SHA-256 exchange used when the server advertises `AuthenticationSASL`
+//! rather than `AuthenticationSha256Password`.
+
+use super::sha256::{get_key_from_hmac, xor_between_password};
+use crypto::digest::Digest as Sha256Digest;
+use crypto::sha2::Sha256;
+use ring::pbkdf2::{self, PBKDF2_HMAC_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt;
+use std::num::NonZeroU32;
+
+/// The SASL mechanism name advertised by servers that support this exchange.
+pub const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+
+const NONCE_LEN: usize = 24;
+const BASE64_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// An error produced while running the SCRAM-SHA-256 exchange.
+#[derive(Debug)]
+pub enum SaslError {
+    /// The server's nonce did not extend the client's nonce.
+    InvalidNonce,
+    /// A server message could not be parsed.
+    InvalidMessage(&'static str),
+    /// The server's final signature did not match the one computed by the client, meaning the
+    /// server (or a man-in-the-middle) does not know the client's password.
+    ServerSignatureMismatch,
+}
+
+impl fmt::Display for SaslError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaslError::InvalidNonce => write!(fmt, "server nonce does not extend client nonce"),
+            SaslError::InvalidMessage(m) => write!(fmt, "invalid SASL message: {}", m),
+            SaslError::ServerSignatureMismatch => {
+                write!(fmt, "SCRAM server signature does not match")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaslError {}
+
+enum State {
+    Initial,
+    AfterFirst { server_signature: Vec<u8> },
+    Done,
+}
+
+/// A client-side SCRAM-SHA-256 exchange, driven message-by-message by the connection handshake.
+pub struct ScramSha256 {
+    password: Vec<u8>,
+    client_nonce: String,
+    client_first_message_bare: String,
+    state: State,
+}
+
+impl ScramSha256 {
+    /// Starts a new exchange for the given password.
+    pub fn new(password: &[u8]) -> ScramSha256 {
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce_bytes)
+            .expect("failed to generate SCRAM client nonce");
+        let client_nonce = base64_encode(&nonce_bytes);
+
+        let client_first_message_bare = format!("n=,r={}", client_nonce);
+
+        ScramSha256 {
+            password: password.to_vec(),
+            client_nonce,
+            client_first_message_bare,
+            state: State::Initial,
+        }
+    }
+
+    /// Returns the `client-first-message` to send to the server.
+    pub fn client_first_message(&self) -> Vec<u8> {
+        format!("n,,{}", self.client_first_message_bare).into_bytes()
+    }
+
+    /// Consumes the server's `server-first-message` and returns the `client-final-message`.
+    pub fn update(&mut self, server_first_message: &[u8]) -> Result<Vec<u8>, SaslError> {
+        let server_first_message = std::str::from_utf8(server_first_message)
+            .map_err(|_| SaslError::InvalidMessage("server-first-message is not UTF-8"))?;
+
+        let mut combined_nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+        for part in server_first_message.split(',') {
+            if let Some(r) = part.strip_prefix("r=") {
+                combined_nonce = Some(r);
+            } else if let Some(s) = part.strip_prefix("s=") {
+                salt = Some(s);
+            } else if let Some(i) = part.strip_prefix("i=") {
+                iterations = Some(i);
+            }
+        }
+
+        let combined_nonce =
+            combined_nonce.ok_or(SaslError::InvalidMessage("server-first-message missing r"))?;
+        let salt = salt.ok_or(SaslError::InvalidMessage("server-first-message missing s"))?;
+        let iterations = iterations
+            .ok_or(SaslError::InvalidMessage("server-first-message missing i"))?
+            .parse::<u32>()
+            .map_err(|_| SaslError::InvalidMessage("i is not a valid integer"))?;
+
+        if !combined_nonce.starts_with(&self.client_nonce) {
+            return Err(SaslError::InvalidNonce);
+        }
+
+        let salt = base64_decode(salt)
+            .ok_or(SaslError::InvalidMessage("s is not valid base64"))?;
+
+        let mut salted_password = [0u8; 32];
+        pbkdf2::derive(
+            PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(iterations).ok_or(SaslError::InvalidMessage("i must be nonzero"))?,
+            &salt,
+            &self.password,
+            &mut salted_password,
+        );
+
+        let client_key = get_key_from_hmac(&salted_password, b"Client Key");
+        let client_key_bytes = client_key.as_ref();
+
+        let mut hasher = Sha256::new();
+        hasher.input(client_key_bytes);
+        let mut stored_key = [0u8; 32];
+        hasher.result(&mut stored_key);
+
+        let channel_binding = "c=biws"; // base64("n,,"), we never request channel binding
+        let client_final_message_without_proof =
+            format!("{},r={}", channel_binding, combined_nonce);
+
+        let auth_message = format!(
+            "{},{},{}",
+            self.client_first_message_bare, server_first_message, client_final_message_without_proof
+        );
+
+        let client_signature = get_key_from_hmac(&stored_key, auth_message.as_bytes());
+        let client_proof = xor_between_password(
+            client_key_bytes,
+            client_signature.as_ref(),
+            client_key_bytes.len(),
+        );
+
+        let server_key = get_key_from_hmac(&salted_password, b"Server Key");
+        let server_signature = get_key_from_hmac(server_key.as_ref(), auth_message.as_bytes())
+            .as_ref()
+            .to_vec();
+
+        self.state = State::AfterFirst { server_signature };
+
+        Ok(format!(
+            "{},p={}",
+            client_final_message_without_proof,
+            base64_encode(&client_proof)
+        )
+        .into_bytes())
+    }
+
+    /// Verifies the server's `server-final-message`, completing the exchange.
+    pub fn finish(&mut self, server_final_message: &[u8]) -> Result<(), SaslError> {
+        let expected = match std::mem::replace(&mut self.state, State::Done) {
+            State::AfterFirst { server_signature } => server_signature,
+            _ => return Err(SaslError::InvalidMessage("finish called out of order")),
+        };
+
+        let server_final_message = std::str::from_utf8(server_final_message)
+            .map_err(|_| SaslError::InvalidMessage("server-final-message is not UTF-8"))?;
+
+        let signature = server_final_message
+            .strip_prefix("v=")
+            .ok_or(SaslError::InvalidMessage("server-final-message missing v"))?;
+        let signature = base64_decode(signature)
+            .ok_or(SaslError::InvalidMessage("v is not valid base64"))?;
+
+        if signature != expected {
+            return Err(SaslError::ServerSignatureMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        out.push(BASE64_CHARS[(b0 >> 2) as usize] as char);
+        out.push(BASE64_CHARS[(((b0 & 0x03) << 4) | (b1.unwrap_or(0) >> 4)) as usize] as char);
+        out.push(match b1 {
+            Some(b1) => BASE64_CHARS[(((b1 & 0x0f) << 2) | (b2.unwrap_or(0) >> 6)) as usize] as char,
+            None => '=',
+        });
+        out.push(match b2 {
+            Some(b2) => BASE64_CHARS[(b2 & 0x3f) as usize] as char,
+            None => '=',
+        });
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4 + 3);
+    let mut buf = 0u32;
+    let mut bits = 0u32;
+
+    for c in s.bytes() {
+        let value = BASE64_CHARS.iter().position(|&b| b == c)? as u32;
+        buf = (buf << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SALT: &[u8] = b"0123456789abcdef";
+    const ITERATIONS: u32 = 4096;
+
+    /// Plays the server side of the exchange by hand: builds the `server-first-message` answering
+    /// `client_first`, extending its nonce and carrying `SALT` and `ITERATIONS`.
+    fn server_first_message(client_first: &[u8]) -> String {
+        let client_first = std::str::from_utf8(client_first).unwrap();
+        let client_nonce = client_first.strip_prefix("n,,n=,r=").unwrap();
+        let server_nonce = format!("{}server-extension", client_nonce);
+        format!(
+            "r={},s={},i={}",
+            server_nonce,
+            base64_encode(SALT),
+            ITERATIONS
+        )
+    }
+
+    /// Computes the `ServerSignature` the real server would send back in its
+    /// `server-final-message`, given the same transcript the client used to compute its proof.
+    fn server_signature(
+        password: &[u8],
+        client_first_message_bare: &str,
+        server_first_message: &str,
+        client_final_message_without_proof: &str,
+    ) -> Vec<u8> {
+        let mut salted_password = [0u8; 32];
+        pbkdf2::derive(
+            PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(ITERATIONS).unwrap(),
+            SALT,
+            password,
+            &mut salted_password,
+        );
+
+        let server_key = get_key_from_hmac(&salted_password, b"Server Key");
+        let auth_message = format!(
+            "{},{},{}",
+            client_first_message_bare, server_first_message, client_final_message_without_proof
+        );
+        get_key_from_hmac(server_key.as_ref(), auth_message.as_bytes())
+            .as_ref()
+            .to_vec()
+    }
+
+    fn without_proof(client_final_message: &[u8]) -> String {
+        let client_final_message = std::str::from_utf8(client_final_message).unwrap();
+        client_final_message.rsplit_once(",p=").unwrap().0.to_string()
+    }
+
+    #[test]
+    fn full_exchange_with_correct_server_succeeds() {
+        let password = b"correct horse battery staple";
+        let mut client = ScramSha256::new(password);
+
+        let first = client.client_first_message();
+        let client_first_message_bare = client.client_first_message_bare.clone();
+        let server_first = server_first_message(&first);
+
+        let client_final = client.update(server_first.as_bytes()).unwrap();
+        let signature = server_signature(
+            password,
+            &client_first_message_bare,
+            &server_first,
+            &without_proof(&client_final),
+        );
+
+        let server_final = format!("v={}", base64_encode(&signature));
+        client.finish(server_final.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn tampered_server_signature_is_rejected() {
+        let password = b"correct horse battery staple";
+        let mut client = ScramSha256::new(password);
+
+        let first = client.client_first_message();
+        let server_first = server_first_message(&first);
+        client.update(server_first.as_bytes()).unwrap();
+
+        let bogus_signature = base64_encode(b"not the real signature!!");
+        let err = client
+            .finish(format!("v={}", bogus_signature).as_bytes())
+            .unwrap_err();
+        assert!(matches!(err, SaslError::ServerSignatureMismatch));
+    }
+
+    #[test]
+    fn server_nonce_must_extend_client_nonce() {
+        let mut client = ScramSha256::new(b"correct horse battery staple");
+        let _ = client.client_first_message();
+
+        let server_first = format!("r={},s={},i={}", "totally-unrelated-nonce", base64_encode(SALT), ITERATIONS);
+        let err = client.update(server_first.as_bytes()).unwrap_err();
+        assert!(matches!(err, SaslError::InvalidNonce));
+    }
+}
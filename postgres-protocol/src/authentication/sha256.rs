@@ -80,7 +80,7 @@ pub fn rfc5802_algorithm(password: &[u8], body: AuthenticationSha256PasswordBody
 /// for example: 10(0b1010) -> a(97=0b01100001)
 ///
 /// A number will be split to two chars, for example: 26(0b0001_1010) -> 1a
-fn bytes_to_hex(h: &[u8]) -> Vec<u8> {
+pub(crate) fn bytes_to_hex(h: &[u8]) -> Vec<u8> {
     let mut result: Vec<u8> = Vec::with_capacity(h.len() * 2);
     let mut i = 0;
     while i < h.len() {
@@ -93,7 +93,7 @@ fn bytes_to_hex(h: &[u8]) -> Vec<u8> {
 }
 
 /// XOR between two passwords
-fn xor_between_password(password1: &[u8], password2: &[u8], length: usize) -> Vec<u8> {
+pub(crate) fn xor_between_password(password1: &[u8], password2: &[u8], length: usize) -> Vec<u8> {
     let mut result: Vec<u8> = Vec::with_capacity(length);
     let mut i = 0;
     while i < length {
@@ -104,7 +104,7 @@ fn xor_between_password(password1: &[u8], password2: &[u8], length: usize) -> Ve
 }
 
 /// SHA256
-fn get_key_from_hmac(key: &[u8], data: &[u8]) -> Tag {
+pub(crate) fn get_key_from_hmac(key: &[u8], data: &[u8]) -> Tag {
     let key2 = hmac::Key::new(hmac::HMAC_SHA256, key);
     hmac::sign(&key2, data)
 }
@@ -113,7 +113,7 @@ fn get_key_from_hmac(key: &[u8], data: &[u8]) -> Tag {
 /// for example: a(97=0b01100001) -> 10(0b1010)
 ///
 /// two chars will be merged as a number, for example: 1a -> 26(0b0001_1010)
-fn to_hex_byte(hex_char: &[u8]) -> Vec<u8> {
+pub(crate) fn to_hex_byte(hex_char: &[u8]) -> Vec<u8> {
     let mut i = 0;
     let length = hex_char.len() / 2;
     let mut result: Vec<u8> = Vec::with_capacity(length);
@@ -0,0 +1,52 @@
+//! SM3-based RFC5802 authentication support.
+//!
+//! Used instead of [`super::sha256::rfc5802_algorithm`] when the server's
+//! `password_encryption_type` GUC is configured to hash credentials with the SM3 digest rather
+//! than SHA256. The overall RFC5802 structure (PBKDF2-derived salted password, `ClientKey`,
+//! `StoredKey`, the final HMAC/XOR against the server's token) is unchanged; only the digest and
+//! HMAC primitive used for the final steps differ.
+use super::sha256::{bytes_to_hex, to_hex_byte, xor_between_password};
+use crate::message::backend::AuthenticationSha256PasswordBody;
+use hmac::{Hmac, Mac};
+use ring::pbkdf2::{self, PBKDF2_HMAC_SHA1};
+use sm3::{Digest, Sm3};
+use std::num::NonZeroU32;
+
+type HmacSm3 = Hmac<Sm3>;
+
+/// Hashes authentication information using SM3 in place of SHA256, in response to an
+/// `AuthenticationSha256PasswordBody` message sent by a server whose `password_encryption_type`
+/// is set to use SM3.
+///
+/// The resulting string should be sent back to the database in a `PasswordMessage` message.
+#[inline]
+pub fn rfc5802_algorithm(password: &[u8], body: AuthenticationSha256PasswordBody) -> Vec<u8> {
+    let salt = to_hex_byte(&body.random64code());
+    let mut salted_password = [0u8; 32];
+    pbkdf2::derive(
+        PBKDF2_HMAC_SHA1,
+        NonZeroU32::new(body.server_iteration()).unwrap(),
+        &salt,
+        password,
+        &mut salted_password,
+    );
+
+    let client_key = hmac_sm3(&salted_password, "Client Key".as_bytes());
+    let client_key_byte = &client_key;
+
+    let mut hasher = Sm3::new();
+    hasher.update(client_key_byte);
+    let stored_key = hasher.finalize();
+
+    let tokenbyte = to_hex_byte(&body.token());
+
+    let hmac_result = hmac_sm3(&stored_key, &tokenbyte);
+    let h = xor_between_password(&hmac_result, client_key_byte, client_key_byte.len());
+    bytes_to_hex(&h)
+}
+
+fn hmac_sm3(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSm3::new_from_slice(key).expect("HMAC can take a key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
@@ -0,0 +1,4 @@
+//! Low-level openGauss/PostgreSQL wire protocol support shared by client implementations.
+
+pub mod authentication;
+pub mod message;
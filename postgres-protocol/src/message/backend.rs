@@ -0,0 +1,423 @@
+//! Parsing of backend (server-to-client) messages.
+
+use std::io;
+use std::str;
+
+use crate::authentication::Rfc5802Digest;
+
+/// openGauss's extension authentication-request code carrying an `rfc5802_algorithm` challenge,
+/// distinct from the standard libpq codes (0-12) handled alongside it.
+const AUTH_REQ_RFC5802: i32 = 1000;
+
+/// The challenge carried by an `AuthenticationSha256Password` request.
+///
+/// See [`crate::authentication::rfc5802_algorithm`].
+#[derive(Debug, Clone)]
+pub struct AuthenticationSha256PasswordBody {
+    random64code: Vec<u8>,
+    token: Vec<u8>,
+    server_iteration: u32,
+}
+
+impl AuthenticationSha256PasswordBody {
+    /// Builds a body from its wire-format fields.
+    pub fn new(random64code: [u8; 64], token: [u8; 8], server_iteration: [u8; 4]) -> Self {
+        AuthenticationSha256PasswordBody {
+            random64code: random64code.to_vec(),
+            token: token.to_vec(),
+            server_iteration: u32::from_be_bytes(server_iteration),
+        }
+    }
+
+    /// The 64-byte random salt sent by the server.
+    pub fn random64code(&self) -> Vec<u8> {
+        self.random64code.clone()
+    }
+
+    /// The 8-byte token sent by the server.
+    pub fn token(&self) -> Vec<u8> {
+        self.token.clone()
+    }
+
+    /// The PBKDF2 iteration count.
+    pub fn server_iteration(&self) -> u32 {
+        self.server_iteration
+    }
+}
+
+/// A parsed backend message.
+#[derive(Debug)]
+pub enum Message {
+    /// Authentication succeeded.
+    AuthenticationOk,
+    /// The server wants the password sent back in the clear.
+    AuthenticationCleartextPassword,
+    /// The server wants an MD5-hashed password, salted with `salt`.
+    AuthenticationMd5Password { salt: [u8; 4] },
+    /// The server wants an openGauss `rfc5802_algorithm` response to `body`, using `digest`.
+    AuthenticationSha256Password {
+        body: AuthenticationSha256PasswordBody,
+        digest: Rfc5802Digest,
+    },
+    /// The server wants a SASL exchange using one of `mechanisms`.
+    AuthenticationSasl { mechanisms: Vec<String> },
+    /// A SASL `server-first-message` (or any later challenge).
+    AuthenticationSaslContinue { data: Vec<u8> },
+    /// The SASL `server-final-message`.
+    AuthenticationSaslFinal { data: Vec<u8> },
+    /// A runtime parameter report.
+    ParameterStatus { name: String, value: String },
+    /// The process ID/secret key to use for `CancelRequest`.
+    BackendKeyData { process_id: i32, secret_key: i32 },
+    /// The server is ready for a new query; `status` is `b'I'`/`b'T'`/`b'E'` (idle/in
+    /// transaction/failed transaction).
+    ReadyForQuery { status: u8 },
+    /// The column names of the rows that follow.
+    RowDescription { fields: Vec<String> },
+    /// One row of query results, in text format.
+    DataRow { values: Vec<Option<String>> },
+    /// A command completed; `tag` is e.g. `"SELECT 1"`.
+    CommandComplete { tag: String },
+    /// The simple query string was empty.
+    EmptyQueryResponse,
+    /// The server reported an error; `code` is the SQLSTATE.
+    ErrorResponse { code: String, message: String },
+    /// The server reported a notice; `code` is the SQLSTATE.
+    NoticeResponse { code: String, message: String },
+    /// A `Parse` message completed successfully.
+    ParseComplete,
+    /// A `Bind` message completed successfully.
+    BindComplete,
+    /// A `Describe` of a portal found no result columns.
+    NoData,
+    /// A message this module doesn't otherwise model.
+    Unknown { tag: u8 },
+}
+
+impl Message {
+    /// Attempts to parse one message from the front of `buf`.
+    ///
+    /// Returns `Ok(None)` if `buf` does not yet contain a full message — the caller should read
+    /// more bytes and retry — otherwise the parsed message and the number of bytes it occupies at
+    /// the front of `buf`.
+    pub fn parse(buf: &[u8]) -> io::Result<Option<(Message, usize)>> {
+        if buf.len() < 5 {
+            return Ok(None);
+        }
+
+        let tag = buf[0];
+        let len = i32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]);
+        if len < 4 {
+            return Err(invalid("message length must be at least 4"));
+        }
+        let total = 1 + len as usize;
+        if buf.len() < total {
+            return Ok(None);
+        }
+
+        let body = &buf[5..total];
+        let message = match tag {
+            b'R' => parse_authentication(body)?,
+            b'S' => {
+                let [name, value] = split_cstrings(body)?;
+                Message::ParameterStatus { name, value }
+            }
+            b'K' => {
+                if body.len() != 8 {
+                    return Err(invalid("invalid BackendKeyData body"));
+                }
+                Message::BackendKeyData {
+                    process_id: i32::from_be_bytes(body[0..4].try_into().unwrap()),
+                    secret_key: i32::from_be_bytes(body[4..8].try_into().unwrap()),
+                }
+            }
+            b'Z' => {
+                if body.len() != 1 {
+                    return Err(invalid("invalid ReadyForQuery body"));
+                }
+                Message::ReadyForQuery { status: body[0] }
+            }
+            b'T' => Message::RowDescription {
+                fields: parse_row_description(body)?,
+            },
+            b'D' => Message::DataRow {
+                values: parse_data_row(body)?,
+            },
+            b'C' => Message::CommandComplete {
+                tag: cstring(body)?.0,
+            },
+            b'I' => Message::EmptyQueryResponse,
+            b'E' => {
+                let (code, message) = parse_notice_fields(body)?;
+                Message::ErrorResponse { code, message }
+            }
+            b'N' => {
+                let (code, message) = parse_notice_fields(body)?;
+                Message::NoticeResponse { code, message }
+            }
+            b'1' => Message::ParseComplete,
+            b'2' => Message::BindComplete,
+            b'n' => Message::NoData,
+            other => Message::Unknown { tag: other },
+        };
+
+        Ok(Some((message, total)))
+    }
+}
+
+fn parse_authentication(body: &[u8]) -> io::Result<Message> {
+    if body.len() < 4 {
+        return Err(invalid("truncated authentication message"));
+    }
+    let kind = i32::from_be_bytes(body[0..4].try_into().unwrap());
+    let rest = &body[4..];
+
+    match kind {
+        0 => Ok(Message::AuthenticationOk),
+        3 => Ok(Message::AuthenticationCleartextPassword),
+        5 => {
+            if rest.len() != 4 {
+                return Err(invalid("invalid AuthenticationMD5Password body"));
+            }
+            Ok(Message::AuthenticationMd5Password {
+                salt: rest.try_into().unwrap(),
+            })
+        }
+        10 => Ok(Message::AuthenticationSasl {
+            mechanisms: split_null_terminated_list(rest)?,
+        }),
+        11 => Ok(Message::AuthenticationSaslContinue { data: rest.to_vec() }),
+        12 => Ok(Message::AuthenticationSaslFinal { data: rest.to_vec() }),
+        AUTH_REQ_RFC5802 => {
+            if rest.len() < 76 {
+                return Err(invalid("invalid AuthenticationSha256Password body"));
+            }
+            let random64code: [u8; 64] = rest[0..64].try_into().unwrap();
+            let token: [u8; 8] = rest[64..72].try_into().unwrap();
+            let server_iteration: [u8; 4] = rest[72..76].try_into().unwrap();
+            // A trailing digest name selects SM3 in place of the SHA256 default, per
+            // `password_encryption_type`.
+            let digest = match &rest[76..] {
+                b"sm3" => Rfc5802Digest::Sm3,
+                _ => Rfc5802Digest::Sha256,
+            };
+            Ok(Message::AuthenticationSha256Password {
+                body: AuthenticationSha256PasswordBody::new(random64code, token, server_iteration),
+                digest,
+            })
+        }
+        other => Err(invalid(&format!("unsupported authentication method {}", other))),
+    }
+}
+
+fn parse_row_description(body: &[u8]) -> io::Result<Vec<String>> {
+    if body.len() < 2 {
+        return Err(invalid("truncated RowDescription"));
+    }
+    let count = i16::from_be_bytes([body[0], body[1]]) as usize;
+    let mut rest = &body[2..];
+    let mut fields = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let (name, tail) = cstring(rest)?;
+        rest = tail;
+        // table_oid(4) + column_attnum(2) + type_oid(4) + type_len(2) + type_mod(4) +
+        // format_code(2), none of which this driver currently needs.
+        if rest.len() < 18 {
+            return Err(invalid("truncated RowDescription field metadata"));
+        }
+        rest = &rest[18..];
+        fields.push(name);
+    }
+
+    Ok(fields)
+}
+
+fn parse_data_row(body: &[u8]) -> io::Result<Vec<Option<String>>> {
+    if body.len() < 2 {
+        return Err(invalid("truncated DataRow"));
+    }
+    let count = i16::from_be_bytes([body[0], body[1]]) as usize;
+    let mut rest = &body[2..];
+    let mut values = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        if rest.len() < 4 {
+            return Err(invalid("truncated DataRow column"));
+        }
+        let len = i32::from_be_bytes(rest[0..4].try_into().unwrap());
+        rest = &rest[4..];
+
+        if len < 0 {
+            values.push(None);
+            continue;
+        }
+
+        let len = len as usize;
+        if rest.len() < len {
+            return Err(invalid("truncated DataRow column value"));
+        }
+        let value = str::from_utf8(&rest[..len])
+            .map_err(|_| invalid("non-UTF8 column value"))?
+            .to_string();
+        rest = &rest[len..];
+        values.push(Some(value));
+    }
+
+    Ok(values)
+}
+
+/// Parses the field list shared by `ErrorResponse`/`NoticeResponse`, pulling out the SQLSTATE
+/// (`C`) and human-readable message (`M`) fields.
+fn parse_notice_fields(body: &[u8]) -> io::Result<(String, String)> {
+    let mut rest = body;
+    let mut code = String::new();
+    let mut message = String::new();
+
+    loop {
+        let field_type = *rest.first().ok_or_else(|| invalid("truncated error fields"))?;
+        rest = &rest[1..];
+        if field_type == 0 {
+            break;
+        }
+
+        let (value, tail) = cstring(rest)?;
+        rest = tail;
+        match field_type {
+            b'C' => code = value,
+            b'M' => message = value,
+            _ => {}
+        }
+    }
+
+    Ok((code, message))
+}
+
+/// Reads exactly `N` consecutive null-terminated strings, erroring if any are missing or trailing
+/// bytes remain.
+fn split_cstrings<const N: usize>(body: &[u8]) -> io::Result<[String; N]> {
+    let mut rest = body;
+    let mut out: Vec<String> = Vec::with_capacity(N);
+    for _ in 0..N {
+        let (value, tail) = cstring(rest)?;
+        out.push(value);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(invalid("trailing bytes after strings"));
+    }
+    out.try_into()
+        .map_err(|_| invalid("wrong number of strings"))
+}
+
+/// Reads a single null-terminated string from the front of `body`, returning it along with the
+/// remaining bytes.
+fn cstring(body: &[u8]) -> io::Result<(String, &[u8])> {
+    let nul = body
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| invalid("missing null terminator"))?;
+    let value = str::from_utf8(&body[..nul])
+        .map_err(|_| invalid("non-UTF8 string"))?
+        .to_string();
+    Ok((value, &body[nul + 1..]))
+}
+
+/// Reads consecutive null-terminated strings until an empty one is found (the `AuthenticationSASL`
+/// mechanism-list encoding).
+fn split_null_terminated_list(mut data: &[u8]) -> io::Result<Vec<String>> {
+    let mut out = Vec::new();
+    loop {
+        let (value, tail) = cstring(data)?;
+        if value.is_empty() {
+            break;
+        }
+        out.push(value);
+        data = tail;
+    }
+    Ok(out)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_ready_for_query() {
+        let buf = [b'Z', 0, 0, 0, 5, b'I'];
+        let (message, consumed) = Message::parse(&buf).unwrap().unwrap();
+        assert_eq!(consumed, buf.len());
+        assert!(matches!(message, Message::ReadyForQuery { status: b'I' }));
+    }
+
+    #[test]
+    fn incomplete_message_returns_none() {
+        let buf = [b'Z', 0, 0, 0, 5];
+        assert!(Message::parse(&buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_error_response_code_and_message() {
+        let mut buf = vec![b'E', 0, 0, 0, 0];
+        buf.push(b'C');
+        buf.extend_from_slice(b"57014\0");
+        buf.push(b'M');
+        buf.extend_from_slice(b"canceling statement\0");
+        buf.push(0);
+        let len = (buf.len() - 1) as i32;
+        buf[1..5].copy_from_slice(&len.to_be_bytes());
+
+        let (message, consumed) = Message::parse(&buf).unwrap().unwrap();
+        assert_eq!(consumed, buf.len());
+        match message {
+            Message::ErrorResponse { code, message } => {
+                assert_eq!(code, "57014");
+                assert_eq!(message, "canceling statement");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_data_row_with_null() {
+        let mut buf = vec![b'D', 0, 0, 0, 0];
+        buf.extend_from_slice(&2i16.to_be_bytes());
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.push(b'1');
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        let len = (buf.len() - 1) as i32;
+        buf[1..5].copy_from_slice(&len.to_be_bytes());
+
+        let (message, _) = Message::parse(&buf).unwrap().unwrap();
+        match message {
+            Message::DataRow { values } => {
+                assert_eq!(values, vec![Some("1".to_string()), None]);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn dispatches_sm3_digest_from_trailing_algorithm_name() {
+        let mut buf = vec![b'R', 0, 0, 0, 0];
+        buf.extend_from_slice(&AUTH_REQ_RFC5802.to_be_bytes());
+        buf.extend_from_slice(&[1u8; 64]);
+        buf.extend_from_slice(&[2u8; 8]);
+        buf.extend_from_slice(&4096i32.to_be_bytes());
+        buf.extend_from_slice(b"sm3");
+        let len = (buf.len() - 1) as i32;
+        buf[1..5].copy_from_slice(&len.to_be_bytes());
+
+        let (message, _) = Message::parse(&buf).unwrap().unwrap();
+        match message {
+            Message::AuthenticationSha256Password { digest, .. } => {
+                assert_eq!(digest, Rfc5802Digest::Sm3);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Encoding of frontend (client-to-server) messages.
+
+/// Builds a `StartupMessage` carrying `params` (e.g. `user`, `database`).
+pub fn startup_message<'a, I>(params: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut buf = vec![0, 0, 0, 0];
+    buf.extend_from_slice(&196_608i32.to_be_bytes()); // protocol version 3.0
+
+    for (key, value) in params {
+        buf.extend_from_slice(key.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(value.as_bytes());
+        buf.push(0);
+    }
+    buf.push(0);
+
+    let len = (buf.len() as i32).to_be_bytes();
+    buf[0..4].copy_from_slice(&len);
+    buf
+}
+
+/// Builds a `PasswordMessage` carrying `password` (already hashed, if the auth method requires
+/// that).
+pub fn password_message(password: &[u8]) -> Vec<u8> {
+    tagged(b'p', |buf| {
+        buf.extend_from_slice(password);
+        buf.push(0);
+    })
+}
+
+/// Builds the `SASLInitialResponse` message that starts a SASL exchange.
+pub fn sasl_initial_response(mechanism: &str, data: &[u8]) -> Vec<u8> {
+    tagged(b'p', |buf| {
+        buf.extend_from_slice(mechanism.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+        buf.extend_from_slice(data);
+    })
+}
+
+/// Builds a `SASLResponse` message carrying the next message in the exchange.
+pub fn sasl_response(data: &[u8]) -> Vec<u8> {
+    tagged(b'p', |buf| buf.extend_from_slice(data))
+}
+
+/// Builds a simple-query-protocol `Query` message.
+pub fn query(query: &str) -> Vec<u8> {
+    tagged(b'Q', |buf| {
+        buf.extend_from_slice(query.as_bytes());
+        buf.push(0);
+    })
+}
+
+/// Builds a `Terminate` message.
+pub fn terminate() -> Vec<u8> {
+    tagged(b'X', |_| {})
+}
+
+/// Builds a `CancelRequest`, sent on a fresh connection without a startup handshake to ask the
+/// server to cancel the query running on the connection identified by `process_id`/`secret_key`.
+pub fn cancel_request(process_id: i32, secret_key: i32) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(16);
+    buf.extend_from_slice(&16i32.to_be_bytes());
+    buf.extend_from_slice(&80_877_102i32.to_be_bytes());
+    buf.extend_from_slice(&process_id.to_be_bytes());
+    buf.extend_from_slice(&secret_key.to_be_bytes());
+    buf
+}
+
+/// Writes a tagged message (`tag` followed by a length-prefixed body built by `body`).
+fn tagged(tag: u8, body: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut buf = vec![tag, 0, 0, 0, 0];
+    body(&mut buf);
+    let len = ((buf.len() - 1) as i32).to_be_bytes();
+    buf[1..5].copy_from_slice(&len);
+    buf
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::backend::Message;
+
+    #[test]
+    fn query_message_round_trips_through_parse() {
+        let buf = query("SELECT 1");
+        let (message, consumed) = Message::parse(&buf).unwrap().unwrap();
+        assert_eq!(consumed, buf.len());
+        assert!(matches!(message, Message::Unknown { tag: b'Q' }));
+    }
+
+    #[test]
+    fn startup_message_has_no_tag_byte() {
+        let buf = startup_message([("user", "postgres")]);
+        let len = i32::from_be_bytes(buf[0..4].try_into().unwrap()) as usize;
+        assert_eq!(len, buf.len());
+        assert_eq!(&buf[4..8], &196_608i32.to_be_bytes());
+        assert!(buf.ends_with(b"\0"));
+    }
+
+    #[test]
+    fn cancel_request_has_the_fixed_special_code() {
+        let buf = cancel_request(42, 99);
+        assert_eq!(buf.len(), 16);
+        assert_eq!(&buf[4..8], &80_877_102i32.to_be_bytes());
+        assert_eq!(i32::from_be_bytes(buf[8..12].try_into().unwrap()), 42);
+        assert_eq!(i32::from_be_bytes(buf[12..16].try_into().unwrap()), 99);
+    }
+}
@@ -0,0 +1,4 @@
+//! Wire-level message encoding and decoding.
+
+pub mod backend;
+pub mod frontend;
@@ -0,0 +1,48 @@
+//! Out-of-band query cancellation.
+
+use tokio::io::AsyncWriteExt;
+
+use postgres_protocol::message::frontend;
+
+use crate::config::{Host, SslMode};
+use crate::connect_socket::connect_socket;
+use crate::connect_tls::{negotiate_ssl, Negotiated};
+use crate::error::Error;
+use crate::tls::{MaybeTlsStream, TlsConnect};
+
+/// A handle that can be used to ask the server to cancel an in-progress query on a particular
+/// connection, from a different task or thread than the one running that connection.
+#[derive(Clone, Debug)]
+pub struct CancelToken {
+    pub(crate) host: Host,
+    pub(crate) port: u16,
+    pub(crate) ssl_mode: SslMode,
+    pub(crate) process_id: i32,
+    pub(crate) secret_key: i32,
+}
+
+impl CancelToken {
+    /// Sends a cancellation request to the server.
+    ///
+    /// Opens a fresh connection to the server and sends a `CancelRequest` message carrying the
+    /// process ID and secret key captured from the original connection's startup handshake.
+    pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
+    where
+        T: TlsConnect<crate::connect_socket::Socket>,
+    {
+        let socket = connect_socket(&self.host, self.port, None).await?;
+        let mut stream = match negotiate_ssl(socket, self.ssl_mode, tls).await? {
+            Negotiated::Stream(stream) => stream,
+            Negotiated::RetryPlaintext => {
+                MaybeTlsStream::Raw(connect_socket(&self.host, self.port, None).await?)
+            }
+        };
+
+        stream
+            .write_all(&frontend::cancel_request(self.process_id, self.secret_key))
+            .await?;
+        stream.flush().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! The client handle applications interact with.
+
+use std::time::Instant;
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::cancel_token::CancelToken;
+use crate::config::{Host, SslMode};
+use crate::connection::Request;
+use crate::error::Error;
+use crate::metrics::{Metrics, QueryOutcome};
+
+/// A prepared statement handle returned by [`Client::prepare`].
+///
+/// Statements are client-side only: binding happens by substituting parameters into the
+/// statement text as SQL literals (see [`ToSql`]) rather than through the wire protocol's binary
+/// `Bind` format.
+#[derive(Debug, Clone)]
+pub struct Statement(pub(crate) String);
+
+/// A row returned by [`Client::query`].
+#[derive(Debug, Clone)]
+pub struct Row(Vec<Option<String>>);
+
+impl Row {
+    pub(crate) fn new(values: Vec<Option<String>>) -> Row {
+        Row(values)
+    }
+
+    /// Deserializes the column at `idx`.
+    ///
+    /// Panics if `idx` is out of bounds or the column's text representation can't be parsed as
+    /// `T`, matching the convenience `get` offered by other postgres client libraries.
+    pub fn get<T>(&self, idx: usize) -> T
+    where
+        T: FromSql,
+    {
+        T::from_sql_text(self.0[idx].as_deref())
+    }
+}
+
+/// A value that can be deserialized from a column's text-format representation.
+pub trait FromSql: Sized {
+    /// Parses `raw`, which is `None` for a SQL `NULL`.
+    fn from_sql_text(raw: Option<&str>) -> Self;
+}
+
+macro_rules! impl_from_sql_parse {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql_text(raw: Option<&str>) -> Self {
+                    let raw = raw.expect("unexpected NULL for a non-Option column");
+                    raw.parse()
+                        .unwrap_or_else(|e| panic!("invalid {} value `{}`: {}", stringify!($t), raw, e))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_sql_parse!(i16, i32, i64, f32, f64, bool, String);
+
+impl<T: FromSql> FromSql for Option<T> {
+    fn from_sql_text(raw: Option<&str>) -> Self {
+        raw.map(|_| T::from_sql_text(raw))
+    }
+}
+
+/// A value that can be bound to a query parameter.
+///
+/// Parameters are substituted into the query text as SQL literals (see [`Statement`]); this
+/// trait only has to know how to render itself as one, quoting/escaping string-like values so
+/// they're safe to splice in verbatim.
+pub trait ToSql {
+    /// Renders this value as a SQL literal.
+    fn to_sql_literal(&self) -> String;
+}
+
+macro_rules! impl_to_sql_display {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl ToSql for $t {
+                fn to_sql_literal(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_to_sql_display!(i16, i32, i64, f32, f64, bool);
+
+// `str` is unsized, so `impl ToSql for str` could never actually be used behind a `&dyn ToSql`
+// parameter (Rust doesn't allow unsizing a `&str` to `&dyn ToSql` in one step); implementing it
+// for `&str` instead is what lets string literals be passed directly.
+impl ToSql for &str {
+    fn to_sql_literal(&self) -> String {
+        format!("'{}'", self.replace('\'', "''"))
+    }
+}
+
+impl ToSql for String {
+    fn to_sql_literal(&self) -> String {
+        self.as_str().to_sql_literal()
+    }
+}
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql_literal(&self) -> String {
+        match self {
+            Some(v) => v.to_sql_literal(),
+            None => "NULL".to_string(),
+        }
+    }
+}
+
+/// An asynchronous handle to an openGauss connection.
+///
+/// The handle communicates with the background task produced alongside it by [`crate::connect`]
+/// over an internal channel; the task owns the actual socket, and queries issued here are
+/// forwarded to it.
+pub struct Client {
+    host: Host,
+    port: u16,
+    ssl_mode: SslMode,
+    process_id: i32,
+    secret_key: i32,
+    metrics: Metrics,
+    sender: mpsc::UnboundedSender<Request>,
+}
+
+impl Client {
+    pub(crate) fn new(
+        sender: mpsc::UnboundedSender<Request>,
+        host: Host,
+        port: u16,
+        ssl_mode: SslMode,
+        process_id: i32,
+        secret_key: i32,
+        metrics: Metrics,
+    ) -> Client {
+        metrics.connection_opened();
+        Client {
+            host,
+            port,
+            ssl_mode,
+            process_id,
+            secret_key,
+            metrics,
+            sender,
+        }
+    }
+
+    /// Returns the connection/query metrics collected on this client.
+    ///
+    /// Install a [`crate::metrics::MetricsSink`] (e.g. one bridging into a Prometheus registry)
+    /// via [`crate::config::Config::metrics_sink`] before connecting to have every event
+    /// forwarded there as well.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Creates a new prepared statement.
+    pub async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+        // Statements here are purely client-side, so there's no server-side statement cache to
+        // hit; once one lands, this is where `record_prepare_cache` would report a hit instead.
+        self.metrics.record_prepare_cache(false);
+        Ok(Statement(query.to_string()))
+    }
+
+    /// Executes a statement, returning the resulting rows.
+    pub async fn query(&self, statement: &Statement, params: &[&dyn ToSql]) -> Result<Vec<Row>, Error> {
+        let start = Instant::now();
+        let result = self.query_inner(statement, params).await;
+        self.record_outcome(&result, start);
+        result
+    }
+
+    async fn query_inner(
+        &self,
+        statement: &Statement,
+        params: &[&dyn ToSql],
+    ) -> Result<Vec<Row>, Error> {
+        let sql = bind_params(&statement.0, params)?;
+        self.execute(sql).await
+    }
+
+    /// Executes a sequence of SQL statements using the simple query protocol.
+    pub async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        let start = Instant::now();
+        let result = self.batch_execute_inner(query).await;
+        self.record_outcome(&result, start);
+        result
+    }
+
+    async fn batch_execute_inner(&self, query: &str) -> Result<(), Error> {
+        self.execute(query.to_string()).await.map(|_| ())
+    }
+
+    async fn execute(&self, query: String) -> Result<Vec<Row>, Error> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Request { query, respond: tx })
+            .map_err(|_| Error::other("the connection's background task has shut down".to_string()))?;
+        rx.await
+            .map_err(|_| Error::other("the connection's background task has shut down".to_string()))?
+    }
+
+    fn record_outcome<T>(&self, result: &Result<T, Error>, start: Instant) {
+        let outcome = match result {
+            Ok(_) => QueryOutcome::Ok,
+            Err(e) => QueryOutcome::Err(e.code().cloned()),
+        };
+        self.metrics.record_query(outcome, start.elapsed());
+    }
+
+    /// Returns a token that can be used to cancel queries running on this connection from another
+    /// task or thread.
+    pub fn cancel_token(&self) -> CancelToken {
+        CancelToken {
+            host: self.host.clone(),
+            port: self.port,
+            ssl_mode: self.ssl_mode,
+            process_id: self.process_id,
+            secret_key: self.secret_key,
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.metrics.connection_closed();
+    }
+}
+
+/// Substitutes `$1`, `$2`, ... placeholders in `query` with the SQL literal rendering of the
+/// corresponding entry in `params`.
+fn bind_params(query: &str, params: &[&dyn ToSql]) -> Result<String, Error> {
+    let mut out = String::with_capacity(query.len());
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+
+        let mut digits = String::new();
+        while let Some(&(_, d)) = chars.peek() {
+            if !d.is_ascii_digit() {
+                break;
+            }
+            digits.push(d);
+            chars.next();
+        }
+
+        if digits.is_empty() {
+            out.push('$');
+            continue;
+        }
+
+        let index: usize = digits.parse().unwrap();
+        let param = index
+            .checked_sub(1)
+            .and_then(|i| params.get(i))
+            .ok_or_else(|| Error::other(format!("no parameter supplied for `${}`", index)))?;
+        out.push_str(&param.to_sql_literal());
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bind_params_substitutes_placeholders() {
+        let a: &dyn ToSql = &1i32;
+        let b: &dyn ToSql = &"it's fine";
+        let sql = bind_params("SELECT $1 WHERE name = $2", &[a, b]).unwrap();
+        assert_eq!(sql, "SELECT 1 WHERE name = 'it''s fine'");
+    }
+
+    #[test]
+    fn bind_params_rejects_missing_parameter() {
+        bind_params("SELECT $1", &[]).unwrap_err();
+    }
+
+    #[test]
+    fn bind_params_leaves_bare_dollar_alone() {
+        let sql = bind_params("SELECT '$'", &[]).unwrap();
+        assert_eq!(sql, "SELECT '$'");
+    }
+}
@@ -0,0 +1,719 @@
+//! Connection configuration.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::metrics::MetricsSink;
+
+/// A host that the driver may attempt to connect to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Host {
+    /// A TCP hostname or IP address.
+    Tcp(String),
+    /// The path to a directory containing a Unix domain socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Properties required of a session in order for it to be acceptable for use, as governed by
+/// `target_session_attrs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetSessionAttrs {
+    /// No special properties are required.
+    Any,
+    /// The session must allow writes.
+    ReadWrite,
+    /// The session must not allow writes.
+    ReadOnly,
+    /// The session must be connected to a primary server (not in recovery).
+    Primary,
+    /// The session must be connected to a standby server (in recovery).
+    Standby,
+    /// Prefer a standby server, but accept any reachable host if none is found.
+    PreferStandby,
+}
+
+/// TLS negotiation behavior, as governed by `sslmode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SslMode {
+    /// Never use TLS.
+    Disable,
+    /// Attempt TLS, falling back to a non-TLS connection if it fails.
+    Prefer,
+    /// Require the use of TLS.
+    Require,
+    /// Require TLS and verify that the server's certificate chains to a trusted CA.
+    ///
+    /// Verification itself is performed by the supplied [`crate::tls::TlsConnect`] connector,
+    /// which receives this mode via [`crate::tls::TlsConnect::connect`] and is expected to do
+    /// chain validation (but not hostname checking) for it.
+    VerifyCa,
+    /// Like `VerifyCa`, and additionally verify that the certificate matches the host being
+    /// connected to.
+    VerifyFull,
+}
+
+/// Configuration of a SOCKS5 proxy that connections should be tunnelled through.
+///
+/// Configured via the `socks_proxy` (`host:port`), `socks_username` and `socks_password`
+/// connection string parameters, or the equivalent `Config` builder methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SocksConfig {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+}
+
+impl SocksConfig {
+    /// The proxy host.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The proxy port.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// Connection configuration.
+#[derive(Clone)]
+pub struct Config {
+    pub(crate) user: Option<String>,
+    pub(crate) password: Option<Vec<u8>>,
+    pub(crate) dbname: Option<String>,
+    pub(crate) options: Option<String>,
+    pub(crate) application_name: Option<String>,
+    pub(crate) ssl_mode: SslMode,
+    pub(crate) host: Vec<Host>,
+    pub(crate) port: Vec<u16>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) keepalives: bool,
+    pub(crate) keepalives_idle: Duration,
+    pub(crate) target_session_attrs: TargetSessionAttrs,
+    pub(crate) socks: Option<SocksConfig>,
+    pub(crate) metrics_sink: Option<Arc<dyn MetricsSink>>,
+}
+
+// `MetricsSink` is a plain trait with no `Debug`/`PartialEq` bound, so these are written by hand
+// rather than derived: `Debug` only prints whether a sink is set, and `PartialEq` ignores the sink,
+// so two configs compare equal regardless of which sink (if any) they carry.
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("user", &self.user)
+            .field("password", &self.password)
+            .field("dbname", &self.dbname)
+            .field("options", &self.options)
+            .field("application_name", &self.application_name)
+            .field("ssl_mode", &self.ssl_mode)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("keepalives", &self.keepalives)
+            .field("keepalives_idle", &self.keepalives_idle)
+            .field("target_session_attrs", &self.target_session_attrs)
+            .field("socks", &self.socks)
+            .field("metrics_sink", &self.metrics_sink.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        self.user == other.user
+            && self.password == other.password
+            && self.dbname == other.dbname
+            && self.options == other.options
+            && self.application_name == other.application_name
+            && self.ssl_mode == other.ssl_mode
+            && self.host == other.host
+            && self.port == other.port
+            && self.connect_timeout == other.connect_timeout
+            && self.keepalives == other.keepalives
+            && self.keepalives_idle == other.keepalives_idle
+            && self.target_session_attrs == other.target_session_attrs
+            && self.socks == other.socks
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
+impl Config {
+    /// Creates a new configuration with default settings.
+    pub fn new() -> Config {
+        Config {
+            user: None,
+            password: None,
+            dbname: None,
+            options: None,
+            application_name: None,
+            ssl_mode: SslMode::Prefer,
+            host: vec![],
+            port: vec![],
+            connect_timeout: None,
+            keepalives: true,
+            keepalives_idle: Duration::from_secs(2 * 60 * 60),
+            target_session_attrs: TargetSessionAttrs::Any,
+            socks: None,
+            metrics_sink: None,
+        }
+    }
+
+    /// Sets the user to authenticate as.
+    pub fn user(&mut self, user: &str) -> &mut Config {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    /// Gets the user to authenticate as, if set.
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Sets the password to authenticate with.
+    pub fn password<T: AsRef<[u8]>>(&mut self, password: T) -> &mut Config {
+        self.password = Some(password.as_ref().to_vec());
+        self
+    }
+
+    /// Gets the password to authenticate with, if set.
+    pub(crate) fn get_password(&self) -> Option<&[u8]> {
+        self.password.as_deref()
+    }
+
+    /// Sets the name of the database to connect to.
+    pub fn dbname(&mut self, dbname: &str) -> &mut Config {
+        self.dbname = Some(dbname.to_string());
+        self
+    }
+
+    /// Gets the name of the database to connect to, if set.
+    pub(crate) fn get_dbname(&self) -> Option<&str> {
+        self.dbname.as_deref()
+    }
+
+    /// Sets extra command-line options to send to the server at connection start.
+    pub fn options(&mut self, options: &str) -> &mut Config {
+        self.options = Some(options.to_string());
+        self
+    }
+
+    /// Gets the extra command-line options to send to the server at connection start, if set.
+    pub(crate) fn get_options(&self) -> Option<&str> {
+        self.options.as_deref()
+    }
+
+    /// Sets the value of the `application_name` runtime parameter.
+    pub fn application_name(&mut self, application_name: &str) -> &mut Config {
+        self.application_name = Some(application_name.to_string());
+        self
+    }
+
+    /// Gets the value of the `application_name` runtime parameter, if set.
+    pub(crate) fn get_application_name(&self) -> Option<&str> {
+        self.application_name.as_deref()
+    }
+
+    /// Sets the SSL negotiation mode.
+    ///
+    /// Defaults to `SslMode::Prefer`.
+    pub fn ssl_mode(&mut self, ssl_mode: SslMode) -> &mut Config {
+        self.ssl_mode = ssl_mode;
+        self
+    }
+
+    /// Gets the SSL negotiation mode.
+    pub fn get_ssl_mode(&self) -> SslMode {
+        self.ssl_mode
+    }
+
+    /// Installs a [`MetricsSink`] that every connection made with this configuration should
+    /// forward its metrics events to (e.g. one bridging into a Prometheus registry).
+    ///
+    /// By default connections only keep in-memory counters, available via
+    /// [`crate::Client::metrics`].
+    pub fn metrics_sink(&mut self, sink: Arc<dyn MetricsSink>) -> &mut Config {
+        self.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Gets the configured metrics sink, if any.
+    pub(crate) fn get_metrics_sink(&self) -> Option<&Arc<dyn MetricsSink>> {
+        self.metrics_sink.as_ref()
+    }
+
+    /// Opens a connection using this configuration, in the same way as [`crate::connect`].
+    pub async fn connect<T>(
+        &self,
+        tls: T,
+    ) -> Result<(crate::Client, crate::Connection<crate::tls::MaybeTlsStream<crate::connect_socket::Socket, T::Stream>>), Error>
+    where
+        T: crate::tls::TlsConnect<crate::connect_socket::Socket> + Clone,
+    {
+        crate::connect::connect_with_config(self, tls).await
+    }
+
+    /// Adds a host to the list of candidate hosts.
+    ///
+    /// Multiple hosts can be specified, either by calling this method multiple times, or by
+    /// specifying a comma-separated list in the `host` connection string parameter. Each host
+    /// must have a corresponding `port` entry, unless only one port is specified, in which case
+    /// it applies to all hosts.
+    pub fn host(&mut self, host: &str) -> &mut Config {
+        self.host.push(Host::Tcp(host.to_string()));
+        self
+    }
+
+    /// Adds a Unix socket host to the list of candidate hosts.
+    #[cfg(unix)]
+    pub fn host_path<T>(&mut self, host: T) -> &mut Config
+    where
+        T: AsRef<std::path::Path>,
+    {
+        self.host.push(Host::Unix(host.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Gets the hosts that have been added to the configuration.
+    pub fn get_hosts(&self) -> &[Host] {
+        &self.host
+    }
+
+    /// Adds a port to connect to.
+    pub fn port(&mut self, port: u16) -> &mut Config {
+        self.port.push(port);
+        self
+    }
+
+    /// Gets the ports that have been added to the configuration.
+    pub fn get_ports(&self) -> &[u16] {
+        &self.port
+    }
+
+    /// Sets the timeout applied to socket-level connection attempts.
+    ///
+    /// There is no timeout by default.
+    pub fn connect_timeout(&mut self, connect_timeout: Duration) -> &mut Config {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Controls the use of TCP keepalive.
+    ///
+    /// Defaults to `true`.
+    pub fn keepalives(&mut self, keepalives: bool) -> &mut Config {
+        self.keepalives = keepalives;
+        self
+    }
+
+    /// Sets the amount of idle time before a keepalive packet is sent on the connection.
+    ///
+    /// Defaults to 2 hours.
+    pub fn keepalives_idle(&mut self, keepalives_idle: Duration) -> &mut Config {
+        self.keepalives_idle = keepalives_idle;
+        self
+    }
+
+    /// Sets the requirements of the session.
+    ///
+    /// Defaults to `TargetSessionAttrs::Any`.
+    pub fn target_session_attrs(
+        &mut self,
+        target_session_attrs: TargetSessionAttrs,
+    ) -> &mut Config {
+        self.target_session_attrs = target_session_attrs;
+        self
+    }
+
+    /// Gets the requirements of the session.
+    pub fn get_target_session_attrs(&self) -> TargetSessionAttrs {
+        self.target_session_attrs
+    }
+
+    /// Sets a SOCKS5 proxy that connections should be tunnelled through.
+    ///
+    /// `host` and `port` identify the proxy itself; the openGauss startup handshake is performed
+    /// against the target host/port configured via [`Config::host`]/[`Config::port`] only after
+    /// the SOCKS5 `CONNECT` handshake against the proxy succeeds.
+    pub fn socks_proxy(&mut self, host: &str, port: u16) -> &mut Config {
+        let username = self.socks.as_ref().and_then(|s| s.username.clone());
+        let password = self.socks.as_ref().and_then(|s| s.password.clone());
+        self.socks = Some(SocksConfig {
+            host: host.to_string(),
+            port,
+            username,
+            password,
+        });
+        self
+    }
+
+    /// Sets the username used to authenticate with the SOCKS5 proxy, if it requires one.
+    pub fn socks_username(&mut self, username: &str) -> &mut Config {
+        self.socks_config_mut().username = Some(username.to_string());
+        self
+    }
+
+    /// Sets the password used to authenticate with the SOCKS5 proxy, if it requires one.
+    pub fn socks_password(&mut self, password: &str) -> &mut Config {
+        self.socks_config_mut().password = Some(password.to_string());
+        self
+    }
+
+    /// Gets the configured SOCKS5 proxy, if any.
+    pub fn get_socks_proxy(&self) -> Option<&SocksConfig> {
+        self.socks.as_ref()
+    }
+
+    fn socks_config_mut(&mut self) -> &mut SocksConfig {
+        self.socks.get_or_insert_with(|| SocksConfig {
+            host: String::new(),
+            port: 1080,
+            username: None,
+            password: None,
+        })
+    }
+
+    fn param(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        match key {
+            "user" => {
+                self.user(value);
+            }
+            "password" => {
+                self.password(value);
+            }
+            "dbname" => {
+                self.dbname(value);
+            }
+            "options" => {
+                self.options(value);
+            }
+            "application_name" => {
+                self.application_name(value);
+            }
+            "sslmode" => {
+                let mode = match value {
+                    "disable" => SslMode::Disable,
+                    "prefer" => SslMode::Prefer,
+                    "require" => SslMode::Require,
+                    "verify-ca" => SslMode::VerifyCa,
+                    "verify-full" => SslMode::VerifyFull,
+                    other => {
+                        return Err(Error::connect(format!("invalid sslmode value: `{}`", other)))
+                    }
+                };
+                self.ssl_mode(mode);
+            }
+            "host" => {
+                for host in value.split(',') {
+                    self.parse_host(host)?;
+                }
+            }
+            "port" => {
+                for port in value.split(',') {
+                    let port = if port.is_empty() {
+                        5432
+                    } else {
+                        port.parse()
+                            .map_err(|_| Error::connect(format!("invalid port value: `{}`", port)))?
+                    };
+                    self.port(port);
+                }
+            }
+            "connect_timeout" => {
+                let timeout = value
+                    .parse::<i64>()
+                    .map_err(|_| Error::connect(format!("invalid connect_timeout value: `{}`", value)))?;
+                if timeout > 0 {
+                    self.connect_timeout(Duration::from_secs(timeout as u64));
+                }
+            }
+            "keepalives" => {
+                let keepalives = value
+                    .parse::<u64>()
+                    .map_err(|_| Error::connect(format!("invalid keepalives value: `{}`", value)))?;
+                self.keepalives(keepalives != 0);
+            }
+            "keepalives_idle" => {
+                let seconds = value.parse::<i64>().map_err(|_| {
+                    Error::connect(format!("invalid keepalives_idle value: `{}`", value))
+                })?;
+                self.keepalives_idle(Duration::from_secs(seconds.max(0) as u64));
+            }
+            "target_session_attrs" => {
+                let attrs = match value {
+                    "any" => TargetSessionAttrs::Any,
+                    "read-write" => TargetSessionAttrs::ReadWrite,
+                    "read-only" => TargetSessionAttrs::ReadOnly,
+                    "primary" => TargetSessionAttrs::Primary,
+                    "standby" => TargetSessionAttrs::Standby,
+                    "prefer-standby" => TargetSessionAttrs::PreferStandby,
+                    other => {
+                        return Err(Error::connect(format!(
+                            "invalid target_session_attrs value: `{}`",
+                            other
+                        )))
+                    }
+                };
+                self.target_session_attrs(attrs);
+            }
+            "socks_proxy" => {
+                let (host, port) = value.rsplit_once(':').ok_or_else(|| {
+                    Error::connect("socks_proxy must be in the form `host:port`".to_string())
+                })?;
+                let port = port
+                    .parse()
+                    .map_err(|_| Error::connect(format!("invalid socks_proxy port: `{}`", port)))?;
+                self.socks_proxy(host, port);
+            }
+            "socks_username" => {
+                self.socks_username(value);
+            }
+            "socks_password" => {
+                self.socks_password(value);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn parse_host(&mut self, s: &str) -> Result<(), Error> {
+        #[cfg(unix)]
+        {
+            if s.starts_with('/') {
+                self.host_path(s);
+                return Ok(());
+            }
+        }
+
+        self.host(s);
+        Ok(())
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Config, Error> {
+        if s.starts_with("opengauss://") || s.starts_with("opengauss:") {
+            parse_url(s)
+        } else {
+            parse_pairs(s)
+        }
+    }
+}
+
+fn parse_pairs(s: &str) -> Result<Config, Error> {
+    let mut config = Config::new();
+
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((_, c)) = chars.peek().copied() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let key = take_while(&mut chars, |c| c != '=' && !c.is_whitespace());
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some((_, '=')) => {}
+            _ => return Err(Error::connect("expected `=`".to_string())),
+        }
+        skip_whitespace(&mut chars);
+
+        let value = if let Some((_, '\'')) = chars.peek().copied() {
+            chars.next();
+            take_quoted(&mut chars)?
+        } else {
+            take_while(&mut chars, |c| !c.is_whitespace())
+        };
+
+        config.param(key.trim(), &value)?;
+    }
+
+    Ok(config)
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>) {
+    while let Some((_, c)) = chars.peek().copied() {
+        if c.is_whitespace() {
+            chars.next();
+        } else {
+            break;
+        }
+    }
+}
+
+fn take_while(
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+    f: impl Fn(char) -> bool,
+) -> String {
+    let mut out = String::new();
+    while let Some((_, c)) = chars.peek().copied() {
+        if f(c) {
+            out.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    out
+}
+
+fn take_quoted(chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>) -> Result<String, Error> {
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some((_, '\'')) => return Ok(out),
+            Some((_, '\\')) => match chars.next() {
+                Some((_, c)) => out.push(c),
+                None => return Err(Error::connect("unterminated escape".to_string())),
+            },
+            Some((_, c)) => out.push(c),
+            None => return Err(Error::connect("unterminated quoted value".to_string())),
+        }
+    }
+}
+
+fn parse_url(s: &str) -> Result<Config, Error> {
+    let mut config = Config::new();
+
+    let rest = s
+        .strip_prefix("opengauss://")
+        .or_else(|| s.strip_prefix("opengauss:"))
+        .unwrap_or(s);
+
+    let (authority_and_path, query) = match rest.split_once('?') {
+        Some((a, q)) => (a, Some(q)),
+        None => (rest, None),
+    };
+
+    let (authority, path) = match authority_and_path.split_once('/') {
+        Some((a, p)) => (a, Some(p)),
+        None => (authority_and_path, None),
+    };
+
+    let (userinfo, hostport) = match authority.rsplit_once('@') {
+        Some((u, h)) => (Some(u), h),
+        None => (None, authority),
+    };
+
+    if let Some(userinfo) = userinfo {
+        let (user, password) = match userinfo.split_once(':') {
+            Some((u, p)) => (u, Some(p)),
+            None => (userinfo, None),
+        };
+        if !user.is_empty() {
+            config.user(&percent_decode(user));
+        }
+        if let Some(password) = password {
+            config.password(percent_decode(password).as_bytes());
+        }
+    }
+
+    if !hostport.is_empty() {
+        for part in hostport.split(',') {
+            let (host, port) = split_host_port(part)?;
+            if !host.is_empty() {
+                config.parse_host(&percent_decode(&host))?;
+                config.port(port.unwrap_or(5432));
+            }
+        }
+    }
+
+    if let Some(path) = path {
+        if !path.is_empty() {
+            config.dbname(&percent_decode(path));
+        }
+    }
+
+    if let Some(query) = query {
+        for pair in query.split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let key = percent_decode(key);
+            let value = percent_decode(value);
+            if key == "host" {
+                for host in value.split(',') {
+                    config.parse_host(host)?;
+                }
+            } else if key == "port" {
+                for port in value.split(',') {
+                    let port = port
+                        .parse()
+                        .map_err(|_| Error::connect(format!("invalid port value: `{}`", port)))?;
+                    config.port(port);
+                }
+            } else {
+                config.param(&key, &value)?;
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+fn split_host_port(s: &str) -> Result<(String, Option<u16>), Error> {
+    if let Some(rest) = s.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| Error::connect("unterminated `[`".to_string()))?;
+        let port = match rest.strip_prefix(':') {
+            Some(p) if !p.is_empty() => Some(
+                p.parse()
+                    .map_err(|_| Error::connect(format!("invalid port value: `{}`", p)))?,
+            ),
+            _ => None,
+        };
+        return Ok((host.to_string(), port));
+    }
+
+    match s.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() => {
+            let port = port
+                .parse()
+                .map_err(|_| Error::connect(format!("invalid port value: `{}`", port)))?;
+            Ok((host.to_string(), Some(port)))
+        }
+        _ => Ok((s.to_string(), None)),
+    }
+}
+
+fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(hex) = std::str::from_utf8(&bytes[i + 1..i + 3]) {
+                if let Ok(value) = u8::from_str_radix(hex, 16) {
+                    out.push(value);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    Cow::Owned(String::from_utf8_lossy(&out).into_owned())
+}
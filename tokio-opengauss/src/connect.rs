@@ -0,0 +1,202 @@
+//! Establishing a connection to an openGauss server.
+
+use std::str::FromStr;
+
+use crate::client::Client;
+use crate::config::{Config, Host, TargetSessionAttrs};
+use crate::connect_raw::connect_raw;
+use crate::connect_socket::{connect_socket, Socket};
+use crate::connect_tls::{negotiate_ssl, Negotiated};
+use crate::connection::Connection;
+use crate::error::Error;
+use crate::socks5;
+use crate::tls::{MaybeTlsStream, TlsConnect};
+
+/// Connects to an openGauss server.
+///
+/// `config` is a libpq-style connection string or `opengauss://` URL, as parsed by
+/// [`Config`]. When multiple hosts are given (`host=a,b` / `opengauss://a,b/`), they're tried in
+/// order and the first host that a working connection can be established to is kept; if
+/// `target_session_attrs` is anything other than `Any`, a host is skipped unless the resulting
+/// session also satisfies it. With `prefer-standby`, the first reachable host that is not a
+/// standby is kept as a fallback and returned if no standby is found.
+///
+/// When a SOCKS5 proxy is configured, the TCP stream to the proxy is established first, and the
+/// SOCKS5 `CONNECT` handshake to the candidate host/port runs before TLS negotiation and the
+/// openGauss startup handshake.
+pub async fn connect<T>(config: &str, tls: T) -> Result<(Client, Connection<MaybeTlsStream<Socket, T::Stream>>), Error>
+where
+    T: TlsConnect<crate::connect_socket::Socket> + Clone,
+{
+    let config = Config::from_str(config)?;
+    connect_with_config(&config, tls).await
+}
+
+pub(crate) async fn connect_with_config<T>(
+    config: &Config,
+    tls: T,
+) -> Result<(Client, Connection<MaybeTlsStream<Socket, T::Stream>>), Error>
+where
+    T: TlsConnect<crate::connect_socket::Socket> + Clone,
+{
+    let hosts = config.get_hosts();
+    let ports = config.get_ports();
+
+    if hosts.is_empty() {
+        return Err(Error::connect("host missing".to_string()));
+    }
+
+    if !(ports.len() == 1 || ports.len() == hosts.len()) {
+        return Err(Error::connect(
+            "the number of ports must be either 1 or the number of hosts".to_string(),
+        ));
+    }
+
+    let attrs = config.get_target_session_attrs();
+    let mut last_err = None;
+    let mut fallback = None;
+
+    for (i, host) in hosts.iter().enumerate() {
+        let port = ports.get(i).or_else(|| ports.first()).copied().unwrap_or(5432);
+
+        let (client, connection, satisfies_attrs) =
+            match connect_host(host, port, config, tls.clone()).await {
+                Ok(t) => t,
+                Err(e) => {
+                    last_err = Some(e);
+                    continue;
+                }
+            };
+
+        if satisfies_attrs {
+            return Ok((client, connection));
+        } else if attrs == TargetSessionAttrs::PreferStandby && fallback.is_none() {
+            // Not a standby, but keep it around in case no standby is reachable at all.
+            fallback = Some((client, connection));
+        } else {
+            last_err = Some(Error::connect(format!(
+                "none of the hosts match the target session attributes ({:?})",
+                attrs
+            )));
+        }
+    }
+
+    if let Some(pair) = fallback {
+        return Ok(pair);
+    }
+
+    Err(last_err.unwrap_or_else(|| Error::connect("could not connect to any host".to_string())))
+}
+
+/// The query that reports the session state `attrs` cares about, or `None` if `attrs` has no
+/// requirement to probe for.
+pub(crate) fn session_probe_query(attrs: TargetSessionAttrs) -> Option<&'static str> {
+    match attrs {
+        TargetSessionAttrs::Any => None,
+        TargetSessionAttrs::ReadWrite | TargetSessionAttrs::ReadOnly => {
+            Some("SHOW transaction_read_only")
+        }
+        TargetSessionAttrs::Primary | TargetSessionAttrs::Standby | TargetSessionAttrs::PreferStandby => {
+            Some("SELECT pg_is_in_recovery()")
+        }
+    }
+}
+
+/// Checks `probe_result` (the text value returned by [`session_probe_query`]) against `attrs`.
+pub(crate) fn attrs_satisfied(attrs: TargetSessionAttrs, probe_result: &str) -> bool {
+    match attrs {
+        TargetSessionAttrs::Any => true,
+        TargetSessionAttrs::ReadWrite => probe_result == "off",
+        TargetSessionAttrs::ReadOnly => probe_result == "on",
+        TargetSessionAttrs::Primary => probe_result == "f",
+        TargetSessionAttrs::Standby | TargetSessionAttrs::PreferStandby => probe_result == "t",
+    }
+}
+
+/// Connects to `host`/`port` and returns the resulting `Client`/`Connection` pair alongside
+/// whether the session satisfies `config`'s `target_session_attrs`.
+async fn connect_host<T>(
+    host: &Host,
+    port: u16,
+    config: &Config,
+    tls: T,
+) -> Result<(Client, Connection<MaybeTlsStream<Socket, T::Stream>>, bool), Error>
+where
+    T: TlsConnect<crate::connect_socket::Socket>,
+{
+    let socket = obtain_socket(host, port, config).await?;
+    let stream = match negotiate_ssl(socket, config.get_ssl_mode(), tls).await? {
+        Negotiated::Stream(stream) => stream,
+        Negotiated::RetryPlaintext => {
+            // The failed TLS handshake consumed the original socket; open a fresh one and
+            // connect over it in plaintext instead of negotiating TLS again.
+            MaybeTlsStream::Raw(obtain_socket(host, port, config).await?)
+        }
+    };
+
+    connect_raw(stream, config, host, port).await
+}
+
+/// Establishes the plain TCP/Unix socket for `host`/`port`, tunnelling through the configured
+/// SOCKS5 proxy first if one is set.
+async fn obtain_socket(host: &Host, port: u16, config: &Config) -> Result<Socket, Error> {
+    let socket = match config.get_socks_proxy() {
+        Some(proxy) => {
+            let proxy_host = Host::Tcp(proxy.host().to_string());
+            let mut socket = connect_socket(&proxy_host, proxy.port(), config.connect_timeout).await?;
+
+            let target_host = match host {
+                Host::Tcp(host) => host.clone(),
+                #[cfg(unix)]
+                Host::Unix(_) => {
+                    return Err(Error::connect(
+                        "a SOCKS5 proxy cannot be used to reach a Unix socket host".to_string(),
+                    ))
+                }
+            };
+            socks5::connect(&mut socket, proxy, &target_host, port).await?;
+            socket
+        }
+        None => connect_socket(host, port, config.connect_timeout).await?,
+    };
+
+    socket.set_keepalive(config.keepalives, config.keepalives_idle)?;
+
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn any_is_always_satisfied() {
+        assert!(session_probe_query(TargetSessionAttrs::Any).is_none());
+    }
+
+    #[test]
+    fn read_write_requires_writable_session() {
+        assert!(attrs_satisfied(TargetSessionAttrs::ReadWrite, "off"));
+        assert!(!attrs_satisfied(TargetSessionAttrs::ReadWrite, "on"));
+    }
+
+    #[test]
+    fn read_only_requires_read_only_session() {
+        assert!(attrs_satisfied(TargetSessionAttrs::ReadOnly, "on"));
+        assert!(!attrs_satisfied(TargetSessionAttrs::ReadOnly, "off"));
+    }
+
+    #[test]
+    fn primary_requires_session_not_in_recovery() {
+        assert!(attrs_satisfied(TargetSessionAttrs::Primary, "f"));
+        assert!(!attrs_satisfied(TargetSessionAttrs::Primary, "t"));
+    }
+
+    #[test]
+    fn standby_and_prefer_standby_require_session_in_recovery() {
+        assert!(attrs_satisfied(TargetSessionAttrs::Standby, "t"));
+        assert!(!attrs_satisfied(TargetSessionAttrs::Standby, "f"));
+        assert!(attrs_satisfied(TargetSessionAttrs::PreferStandby, "t"));
+        assert!(!attrs_satisfied(TargetSessionAttrs::PreferStandby, "f"));
+    }
+}
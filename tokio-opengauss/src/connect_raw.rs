@@ -0,0 +1,202 @@
+//! Runs the openGauss startup handshake (auth, parameter negotiation) over an already-established,
+//! already-TLS-negotiated stream.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+
+use postgres_protocol::authentication::sasl::{ScramSha256, SCRAM_SHA_256};
+use postgres_protocol::authentication::rfc5802_algorithm;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+
+use crate::client::Client;
+use crate::config::{Config, Host};
+use crate::connect::{attrs_satisfied, session_probe_query};
+use crate::connection::{run_simple_query, Connection};
+use crate::error::{Error, SqlState};
+use crate::metrics::Metrics;
+use crate::proto::{write_message, MessageReader};
+
+/// Performs the startup handshake over `stream` and returns the resulting client/connection pair,
+/// alongside whether the session satisfies `config`'s `target_session_attrs`.
+///
+/// `host`/`port` are the candidate this stream was connected to; they're recorded on the returned
+/// `Client` so `Client::cancel_token` knows where to send a `CancelRequest`.
+///
+/// The `target_session_attrs` probe, if any, runs directly against `stream` here rather than
+/// through the `Client`/`Connection` pair: nothing polls `Connection` until the caller spawns it,
+/// so a probe routed through `Client::query` would send a `Request` that's never picked up.
+pub(crate) async fn connect_raw<S>(
+    mut stream: S,
+    config: &Config,
+    host: &Host,
+    port: u16,
+) -> Result<(Client, Connection<S>, bool), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let metrics = match config.get_metrics_sink() {
+        Some(sink) => Metrics::with_sink(sink.clone()),
+        None => Metrics::new(),
+    };
+
+    let user = config
+        .get_user()
+        .ok_or_else(|| Error::connect("user missing".to_string()))?;
+    let mut params = vec![("user", user), ("client_encoding", "UTF8")];
+    if let Some(dbname) = config.get_dbname() {
+        params.push(("database", dbname));
+    }
+    if let Some(application_name) = config.get_application_name() {
+        params.push(("application_name", application_name));
+    }
+    if let Some(options) = config.get_options() {
+        params.push(("options", options));
+    }
+
+    write_message(&mut stream, &frontend::startup_message(params), &metrics).await?;
+
+    let mut process_id = 0;
+    let mut secret_key = 0;
+    let mut scram = None;
+
+    {
+        let mut reader = MessageReader::new(&mut stream);
+
+        loop {
+            match reader.next(&metrics).await? {
+                Message::AuthenticationOk => {}
+                Message::AuthenticationCleartextPassword => {
+                    let password = require_password(config)?;
+                    write_message(
+                        reader.stream_mut(),
+                        &frontend::password_message(password),
+                        &metrics,
+                    )
+                    .await?;
+                }
+                Message::AuthenticationMd5Password { .. } => {
+                    return Err(Error::authentication(
+                        "server requested MD5 authentication, which this driver does not support"
+                            .to_string(),
+                    ));
+                }
+                Message::AuthenticationSha256Password { body, digest } => {
+                    let password = require_password(config)?;
+                    let response = rfc5802_algorithm(digest, password, body);
+                    write_message(
+                        reader.stream_mut(),
+                        &frontend::password_message(&response),
+                        &metrics,
+                    )
+                    .await?;
+                }
+                Message::AuthenticationSasl { mechanisms } => {
+                    if !mechanisms.iter().any(|m| m == SCRAM_SHA_256) {
+                        return Err(Error::authentication(
+                            "server does not support SCRAM-SHA-256".to_string(),
+                        ));
+                    }
+                    let password = require_password(config)?;
+                    let exchange = ScramSha256::new(password);
+                    let first = exchange.client_first_message();
+                    write_message(
+                        reader.stream_mut(),
+                        &frontend::sasl_initial_response(SCRAM_SHA_256, &first),
+                        &metrics,
+                    )
+                    .await?;
+                    scram = Some(exchange);
+                }
+                Message::AuthenticationSaslContinue { data } => {
+                    let exchange = scram
+                        .as_mut()
+                        .ok_or_else(|| Error::authentication("unexpected SASL continue".to_string()))?;
+                    let response = exchange
+                        .update(&data)
+                        .map_err(|e| Error::authentication(e.to_string()))?;
+                    write_message(
+                        reader.stream_mut(),
+                        &frontend::sasl_response(&response),
+                        &metrics,
+                    )
+                    .await?;
+                }
+                Message::AuthenticationSaslFinal { data } => {
+                    let exchange = scram
+                        .as_mut()
+                        .ok_or_else(|| Error::authentication("unexpected SASL final".to_string()))?;
+                    exchange
+                        .finish(&data)
+                        .map_err(|e| Error::authentication(e.to_string()))?;
+                }
+                Message::BackendKeyData {
+                    process_id: pid,
+                    secret_key: key,
+                } => {
+                    process_id = pid;
+                    secret_key = key;
+                }
+                Message::ParameterStatus { .. } => {}
+                Message::ReadyForQuery { .. } => break,
+                Message::ErrorResponse { code, message } => {
+                    return Err(Error::db(SqlState::new(code), message));
+                }
+                other => {
+                    return Err(Error::authentication(format!(
+                        "unexpected message during startup: {:?}",
+                        other
+                    )));
+                }
+            }
+        }
+    }
+
+    let satisfies_attrs = probe_target_session_attrs(&mut stream, config, &metrics).await?;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let connection = Connection::new(stream, receiver, metrics.clone());
+    let client = Client::new(
+        sender,
+        host.clone(),
+        port,
+        config.get_ssl_mode(),
+        process_id,
+        secret_key,
+        metrics,
+    );
+
+    Ok((client, connection, satisfies_attrs))
+}
+
+/// Probes the connected session's recovery/read-only state and checks it against
+/// `config`'s `target_session_attrs`, or returns `true` if it has no requirement to probe for.
+///
+/// Must run before `stream` is handed off to a `Connection`, while it's still safe to drive the
+/// simple query protocol against it directly.
+async fn probe_target_session_attrs<S>(
+    stream: &mut S,
+    config: &Config,
+    metrics: &Metrics,
+) -> Result<bool, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let attrs = config.get_target_session_attrs();
+    let query = match session_probe_query(attrs) {
+        Some(query) => query,
+        None => return Ok(true),
+    };
+
+    let rows = run_simple_query(stream, query, metrics).await?;
+    let row = rows
+        .first()
+        .ok_or_else(|| Error::other(format!("`{}` returned no rows", query)))?;
+    Ok(attrs_satisfied(attrs, &row.get::<String>(0)))
+}
+
+fn require_password(config: &Config) -> Result<&[u8], Error> {
+    config
+        .get_password()
+        .ok_or_else(|| Error::authentication("password missing".to_string()))
+}
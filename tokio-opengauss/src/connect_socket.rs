@@ -0,0 +1,126 @@
+//! Establishing the raw socket a connection attempt runs over.
+
+use std::time::Duration;
+
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::time;
+
+use crate::config::Host;
+use crate::error::Error;
+
+/// Either a TCP or Unix domain socket stream, depending on which kind of [`Host`] was connected.
+pub enum Socket {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Socket {
+    /// Applies `SO_KEEPALIVE`/`TCP_KEEPIDLE` to the underlying socket, if it's a TCP stream.
+    pub(crate) fn set_keepalive(&self, keepalives: bool, idle: Duration) -> Result<(), Error> {
+        let Socket::Tcp(stream) = self else {
+            return Ok(());
+        };
+
+        let sock_ref = SockRef::from(stream);
+        if keepalives {
+            let keepalive = TcpKeepalive::new().with_time(idle);
+            sock_ref.set_tcp_keepalive(&keepalive)?;
+        } else {
+            sock_ref.set_keepalive(false)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Connects a plain (pre-TLS, pre-SOCKS5) socket to `host`/`port`.
+///
+/// When `connect_timeout` is set, the attempt is bounded by it; a timed-out attempt surfaces as a
+/// connection error rather than panicking or hanging.
+pub(crate) async fn connect_socket(
+    host: &Host,
+    port: u16,
+    connect_timeout: Option<Duration>,
+) -> Result<Socket, Error> {
+    match host {
+        Host::Tcp(host) => {
+            let addr = format!("{}:{}", host, port);
+            let stream = connect_with_timeout(TcpStream::connect(addr), connect_timeout).await?;
+            stream.set_nodelay(true)?;
+            Ok(Socket::Tcp(stream))
+        }
+        #[cfg(unix)]
+        Host::Unix(dir) => {
+            let path = dir.join(format!(".s.PGSQL.{}", port));
+            let stream = connect_with_timeout(UnixStream::connect(path), connect_timeout).await?;
+            Ok(Socket::Unix(stream))
+        }
+    }
+}
+
+async fn connect_with_timeout<F, T>(connect: F, timeout: Option<Duration>) -> Result<T, Error>
+where
+    F: std::future::Future<Output = std::io::Result<T>>,
+{
+    match timeout {
+        Some(timeout) => match time::timeout(timeout, connect).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(Error::connect("connection attempt timed out".to_string())),
+        },
+        None => Ok(connect.await?),
+    }
+}
+
+impl tokio::io::AsyncRead for Socket {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(s) => std::pin::Pin::new(s).poll_read(cx, buf),
+            #[cfg(unix)]
+            Socket::Unix(s) => std::pin::Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for Socket {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Socket::Tcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+            #[cfg(unix)]
+            Socket::Unix(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(s) => std::pin::Pin::new(s).poll_flush(cx),
+            #[cfg(unix)]
+            Socket::Unix(s) => std::pin::Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Socket::Tcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+            #[cfg(unix)]
+            Socket::Unix(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Negotiating whether a connection should be upgraded to TLS, as governed by `Config::ssl_mode`.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::SslMode;
+use crate::connect_socket::Socket;
+use crate::error::Error;
+use crate::tls::{MaybeTlsStream, TlsConnect};
+
+/// The startup-time `SSLRequest` message: a length-prefixed request carrying the fixed
+/// "SSL request" code, per the protocol's special-case handshake for negotiating TLS before the
+/// rest of the startup packet is sent.
+const SSL_REQUEST: [u8; 8] = [0, 0, 0, 8, 4, 210, 22, 47];
+
+/// The outcome of [`negotiate_ssl`].
+pub(crate) enum Negotiated<S, T> {
+    /// Negotiation produced a usable stream.
+    Stream(MaybeTlsStream<S, T>),
+    /// `sslmode=prefer` accepted TLS but the handshake itself failed; the failed handshake
+    /// consumed the socket, so the caller must open a fresh one and retry in plaintext.
+    RetryPlaintext,
+}
+
+/// Negotiates TLS over `socket` according to `ssl_mode`, returning either the raw socket or the
+/// TLS-wrapped stream produced by `tls`.
+pub(crate) async fn negotiate_ssl<T>(
+    mut socket: Socket,
+    ssl_mode: SslMode,
+    tls: T,
+) -> Result<Negotiated<Socket, T::Stream>, Error>
+where
+    T: TlsConnect<Socket>,
+{
+    if ssl_mode == SslMode::Disable {
+        return Ok(Negotiated::Stream(MaybeTlsStream::Raw(socket)));
+    }
+
+    let requires_tls = matches!(
+        ssl_mode,
+        SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull
+    );
+
+    if !tls.supported_by_config() {
+        return if requires_tls {
+            Err(Error::tls(
+                "sslmode requires TLS but no TLS connector was configured".to_string(),
+            ))
+        } else {
+            Ok(Negotiated::Stream(MaybeTlsStream::Raw(socket)))
+        };
+    }
+
+    socket.write_all(&SSL_REQUEST).await?;
+    let mut response = [0u8; 1];
+    socket.read_exact(&mut response).await?;
+
+    match response[0] {
+        b'S' => match tls.connect(socket, ssl_mode).await {
+            Ok(stream) => Ok(Negotiated::Stream(MaybeTlsStream::Tls(stream))),
+            Err(e) if requires_tls => Err(e),
+            // `prefer`: the server accepted TLS but the handshake itself failed. The socket was
+            // consumed by the failed handshake, so the caller retries against a fresh one.
+            Err(_) => Ok(Negotiated::RetryPlaintext),
+        },
+        b'N' if requires_tls => Err(Error::tls(
+            "server does not support TLS, but sslmode requires it".to_string(),
+        )),
+        b'N' => Ok(Negotiated::Stream(MaybeTlsStream::Raw(socket))),
+        other => Err(Error::tls(format!(
+            "unexpected response to SSLRequest: {}",
+            other
+        ))),
+    }
+}
@@ -0,0 +1,117 @@
+//! The background task that drives a connection's I/O.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot};
+
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+
+use crate::client::Row;
+use crate::error::{Error, SqlState};
+use crate::metrics::Metrics;
+use crate::proto::{write_message, MessageReader};
+
+/// A query forwarded from a [`crate::Client`] to its background [`Connection`] task.
+pub(crate) struct Request {
+    pub(crate) query: String,
+    pub(crate) respond: oneshot::Sender<Result<Vec<Row>, Error>>,
+}
+
+/// A connection to an openGauss server.
+///
+/// This is a `Future` that must be polled (typically via `tokio::spawn`) for the associated
+/// `Client` to make progress.
+pub struct Connection<S> {
+    inner: Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>,
+    _stream: PhantomData<S>,
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    /// Wraps the background I/O loop that serves `receiver` over `stream`; the loop runs only
+    /// while the returned `Connection` is polled.
+    pub(crate) fn new(
+        stream: S,
+        receiver: mpsc::UnboundedReceiver<Request>,
+        metrics: Metrics,
+    ) -> Connection<S> {
+        Connection {
+            inner: Box::pin(run(stream, receiver, metrics)),
+            _stream: PhantomData,
+        }
+    }
+}
+
+impl<S: Unpin> Future for Connection<S> {
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().inner.as_mut().poll(cx)
+    }
+}
+
+async fn run<S>(
+    mut stream: S,
+    mut receiver: mpsc::UnboundedReceiver<Request>,
+    metrics: Metrics,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(request) = receiver.recv().await {
+        let result = run_simple_query(&mut stream, &request.query, &metrics).await;
+        // The `Client` side may have stopped waiting (e.g. the future was dropped); that's not a
+        // connection-level error.
+        let _ = request.respond.send(result);
+    }
+
+    Ok(())
+}
+
+/// Runs `query` using the simple query protocol, collecting every row from its (possibly several)
+/// `RowDescription`/`DataRow` groups.
+pub(crate) async fn run_simple_query<S>(
+    stream: &mut S,
+    query: &str,
+    metrics: &Metrics,
+) -> Result<Vec<Row>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_message(stream, &frontend::query(query), metrics).await?;
+
+    let mut reader = MessageReader::new(stream);
+    let mut rows = Vec::new();
+    let mut error = None;
+
+    loop {
+        match reader.next(metrics).await? {
+            Message::RowDescription { .. } => {}
+            Message::DataRow { values } => rows.push(Row::new(values)),
+            Message::CommandComplete { .. } | Message::EmptyQueryResponse => {}
+            Message::NoticeResponse { .. } => {}
+            Message::ErrorResponse { code, message } => {
+                error.get_or_insert_with(|| Error::db(SqlState::new(code), message));
+            }
+            Message::ReadyForQuery { .. } => break,
+            other => {
+                return Err(Error::other(format!(
+                    "unexpected message during query execution: {:?}",
+                    other
+                )))
+            }
+        }
+    }
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(rows),
+    }
+}
@@ -0,0 +1,99 @@
+//! Errors.
+
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt;
+
+/// SQLSTATE codes returned by the server, as seen in `ErrorResponse`/`NoticeResponse` bodies.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SqlState(Cow<'static, str>);
+
+impl SqlState {
+    /// `57014` - query_canceled.
+    pub const QUERY_CANCELED: SqlState = SqlState(Cow::Borrowed("57014"));
+
+    /// Creates a `SqlState` from its code.
+    pub fn new(code: String) -> SqlState {
+        SqlState(Cow::Owned(code))
+    }
+
+    /// Returns the error code.
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The error type for this crate.
+#[derive(Debug)]
+pub struct Error(Box<ErrorKind>);
+
+#[derive(Debug)]
+enum ErrorKind {
+    Io(std::io::Error),
+    Connect(String),
+    Tls(String),
+    Authentication(String),
+    Db { code: SqlState, message: String },
+    Other(String),
+}
+
+impl Error {
+    /// Returns the SQLSTATE code associated with this error, if any.
+    pub fn code(&self) -> Option<&SqlState> {
+        match &*self.0 {
+            ErrorKind::Db { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn connect(message: impl Into<String>) -> Error {
+        Error(Box::new(ErrorKind::Connect(message.into())))
+    }
+
+    pub(crate) fn tls(message: impl Into<String>) -> Error {
+        Error(Box::new(ErrorKind::Tls(message.into())))
+    }
+
+    pub(crate) fn authentication(message: impl Into<String>) -> Error {
+        Error(Box::new(ErrorKind::Authentication(message.into())))
+    }
+
+    pub(crate) fn db(code: SqlState, message: impl Into<String>) -> Error {
+        Error(Box::new(ErrorKind::Db {
+            code,
+            message: message.into(),
+        }))
+    }
+
+    pub(crate) fn other(message: impl Into<String>) -> Error {
+        Error(Box::new(ErrorKind::Other(message.into())))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.0 {
+            ErrorKind::Io(e) => write!(fmt, "io error: {}", e),
+            ErrorKind::Connect(m) => write!(fmt, "error connecting to server: {}", m),
+            ErrorKind::Tls(m) => write!(fmt, "error performing TLS handshake: {}", m),
+            ErrorKind::Authentication(m) => write!(fmt, "error authenticating: {}", m),
+            ErrorKind::Db { code, message } => write!(fmt, "db error: {} ({})", message, code.code()),
+            ErrorKind::Other(m) => write!(fmt, "{}", m),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &*self.0 {
+            ErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error(Box::new(ErrorKind::Io(e)))
+    }
+}
@@ -0,0 +1,23 @@
+//! An asynchronous openGauss client.
+
+pub mod cancel_token;
+pub mod client;
+pub mod config;
+mod connect;
+mod connect_raw;
+pub mod connect_socket;
+mod connect_tls;
+mod connection;
+pub mod error;
+pub mod metrics;
+mod proto;
+mod socks5;
+pub mod tls;
+
+pub use crate::cancel_token::CancelToken;
+pub use crate::client::Client;
+pub use crate::config::Config;
+pub use crate::connect::connect;
+pub use crate::connection::Connection;
+pub use crate::error::Error;
+pub use crate::tls::NoTls;
@@ -0,0 +1,208 @@
+//! Built-in connection/query metrics.
+//!
+//! [`Client`](crate::Client) tracks a [`Metrics`] collector covering active connections, queries
+//! issued, bytes sent/received, and prepared-statement cache hits. Read it back with
+//! [`Client::metrics`](crate::Client::metrics), or bridge it into an external system (e.g. a
+//! Prometheus registry) by supplying a [`MetricsSink`] via
+//! [`Config::metrics_sink`](crate::config::Config::metrics_sink) before connecting. Per-query
+//! latency is not kept by [`Metrics`]; it is only passed to the sink's
+//! [`MetricsSink::record_query`].
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::SqlState;
+
+/// A point-in-time snapshot of the counters and gauges tracked by [`Metrics`].
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    /// The number of connections currently open.
+    pub active_connections: u64,
+    /// The total number of queries issued.
+    pub queries_issued: u64,
+    /// The number of those queries that failed.
+    pub queries_failed: u64,
+    /// Bytes written to the connection.
+    pub bytes_sent: u64,
+    /// Bytes read from the connection.
+    pub bytes_received: u64,
+    /// Prepared-statement cache hits.
+    pub prepared_statement_cache_hits: u64,
+    /// Prepared-statement cache misses.
+    pub prepared_statement_cache_misses: u64,
+}
+
+/// The outcome of a query, as reported to [`MetricsSink::record_query`].
+#[derive(Debug, Clone)]
+pub enum QueryOutcome {
+    /// The query completed successfully.
+    Ok,
+    /// The query failed, carrying the server's SQLSTATE if one was reported (e.g.
+    /// `SqlState::QUERY_CANCELED`).
+    Err(Option<SqlState>),
+}
+
+/// A sink that receives metric events as they're recorded.
+///
+/// Implement this to bridge the driver's built-in metrics into an external system, such as a
+/// Prometheus `Registry`, without having to wrap every call site by hand.
+pub trait MetricsSink: Send + Sync {
+    /// Called when a connection is opened (`open = true`) or closed (`open = false`).
+    fn record_connection(&self, open: bool);
+
+    /// Called once a query completes, with its outcome and latency.
+    fn record_query(&self, outcome: QueryOutcome, elapsed: Duration);
+
+    /// Called when bytes are written to/read from the connection.
+    fn record_bytes(&self, sent: u64, received: u64);
+
+    /// Called when a prepared statement is looked up in the statement cache.
+    fn record_prepare_cache(&self, hit: bool);
+}
+
+#[derive(Default)]
+struct Inner {
+    active_connections: AtomicU64,
+    queries_issued: AtomicU64,
+    queries_failed: AtomicU64,
+    queries_failed_by_code: Mutex<HashMap<String, u64>>,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    prepared_statement_cache_hits: AtomicU64,
+    prepared_statement_cache_misses: AtomicU64,
+    sink: Option<Arc<dyn MetricsSink>>,
+}
+
+/// The metrics collector installed on a [`crate::Client`].
+#[derive(Clone)]
+pub struct Metrics(Arc<Inner>);
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    /// Creates a collector that only tracks counters in memory.
+    pub fn new() -> Metrics {
+        Metrics(Arc::new(Inner::default()))
+    }
+
+    /// Creates a collector that also forwards every recorded event to `sink`.
+    pub fn with_sink(sink: Arc<dyn MetricsSink>) -> Metrics {
+        Metrics(Arc::new(Inner {
+            sink: Some(sink),
+            ..Inner::default()
+        }))
+    }
+
+    /// Returns a snapshot of the current counters and gauges.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            active_connections: self.0.active_connections.load(Ordering::Relaxed),
+            queries_issued: self.0.queries_issued.load(Ordering::Relaxed),
+            queries_failed: self.0.queries_failed.load(Ordering::Relaxed),
+            bytes_sent: self.0.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.0.bytes_received.load(Ordering::Relaxed),
+            prepared_statement_cache_hits: self.0.prepared_statement_cache_hits.load(Ordering::Relaxed),
+            prepared_statement_cache_misses: self
+                .0
+                .prepared_statement_cache_misses
+                .load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn connection_opened(&self) {
+        self.0.active_connections.fetch_add(1, Ordering::Relaxed);
+        if let Some(sink) = &self.0.sink {
+            sink.record_connection(true);
+        }
+    }
+
+    pub(crate) fn connection_closed(&self) {
+        self.0.active_connections.fetch_sub(1, Ordering::Relaxed);
+        if let Some(sink) = &self.0.sink {
+            sink.record_connection(false);
+        }
+    }
+
+    pub(crate) fn record_query(&self, outcome: QueryOutcome, elapsed: Duration) {
+        self.0.queries_issued.fetch_add(1, Ordering::Relaxed);
+        if let QueryOutcome::Err(code) = &outcome {
+            self.0.queries_failed.fetch_add(1, Ordering::Relaxed);
+            let key = code.as_ref().map(SqlState::code).unwrap_or("unknown").to_string();
+            *self.0.queries_failed_by_code.lock().unwrap().entry(key).or_insert(0) += 1;
+        }
+        if let Some(sink) = &self.0.sink {
+            sink.record_query(outcome, elapsed);
+        }
+    }
+
+    /// Returns the number of failed queries seen so far, broken down by SQLSTATE (or `"unknown"`
+    /// for failures that didn't carry one).
+    pub fn failures_by_sqlstate(&self) -> HashMap<String, u64> {
+        self.0.queries_failed_by_code.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record_bytes(&self, sent: u64, received: u64) {
+        self.0.bytes_sent.fetch_add(sent, Ordering::Relaxed);
+        self.0.bytes_received.fetch_add(received, Ordering::Relaxed);
+        if let Some(sink) = &self.0.sink {
+            sink.record_bytes(sent, received);
+        }
+    }
+
+    pub(crate) fn record_prepare_cache(&self, hit: bool) {
+        if hit {
+            self.0.prepared_statement_cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.0.prepared_statement_cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(sink) = &self.0.sink {
+            sink.record_prepare_cache(hit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_query_breaks_failures_down_by_sqlstate() {
+        let metrics = Metrics::new();
+
+        metrics.record_query(QueryOutcome::Ok, Duration::ZERO);
+        metrics.record_query(
+            QueryOutcome::Err(Some(SqlState::QUERY_CANCELED)),
+            Duration::ZERO,
+        );
+        metrics.record_query(
+            QueryOutcome::Err(Some(SqlState::QUERY_CANCELED)),
+            Duration::ZERO,
+        );
+        metrics.record_query(QueryOutcome::Err(None), Duration::ZERO);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.queries_issued, 4);
+        assert_eq!(snapshot.queries_failed, 3);
+
+        let failures = metrics.failures_by_sqlstate();
+        assert_eq!(failures.get("57014"), Some(&2));
+        assert_eq!(failures.get("unknown"), Some(&1));
+    }
+
+    #[test]
+    fn record_bytes_accumulates_sent_and_received() {
+        let metrics = Metrics::new();
+        metrics.record_bytes(10, 0);
+        metrics.record_bytes(0, 20);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.bytes_sent, 10);
+        assert_eq!(snapshot.bytes_received, 20);
+    }
+}
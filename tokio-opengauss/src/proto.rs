@@ -0,0 +1,67 @@
+//! Shared message framing used by both the startup handshake and the connection's background I/O
+//! loop.
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use postgres_protocol::message::backend::Message;
+
+use crate::error::Error;
+use crate::metrics::Metrics;
+
+/// Buffers bytes read from a stream and hands back whole backend messages.
+pub(crate) struct MessageReader<'a, S> {
+    stream: &'a mut S,
+    buf: Vec<u8>,
+}
+
+impl<'a, S> MessageReader<'a, S>
+where
+    S: AsyncRead + Unpin,
+{
+    pub(crate) fn new(stream: &'a mut S) -> Self {
+        MessageReader {
+            stream,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Borrows the underlying stream, e.g. to write a response while a read loop holds the
+    /// `MessageReader`.
+    pub(crate) fn stream_mut(&mut self) -> &mut S {
+        self.stream
+    }
+
+    /// Reads the next backend message, performing however many socket reads that takes.
+    pub(crate) async fn next(&mut self, metrics: &Metrics) -> Result<Message, Error> {
+        loop {
+            if let Some((message, consumed)) = Message::parse(&self.buf)? {
+                self.buf.drain(..consumed);
+                return Ok(message);
+            }
+
+            let mut chunk = [0u8; 8192];
+            let n = self.stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Err(Error::connect(
+                    "server closed the connection unexpectedly".to_string(),
+                ));
+            }
+            metrics.record_bytes(0, n as u64);
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+/// Writes `bytes` to `stream` in full, recording them with `metrics`.
+pub(crate) async fn write_message<S>(
+    stream: &mut S,
+    bytes: &[u8],
+    metrics: &Metrics,
+) -> Result<(), Error>
+where
+    S: AsyncWrite + Unpin,
+{
+    stream.write_all(bytes).await?;
+    metrics.record_bytes(bytes.len() as u64, 0);
+    Ok(())
+}
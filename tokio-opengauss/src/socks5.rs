@@ -0,0 +1,159 @@
+//! SOCKS5 proxy handshake.
+//!
+//! Implements just enough of RFC 1928 to let [`crate::connect`] tunnel the openGauss startup
+//! handshake through a SOCKS5 proxy: version/method negotiation, optional username/password
+//! authentication (RFC 1929), and a `CONNECT` request to the real target.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::SocksConfig;
+use crate::error::Error;
+
+const VERSION: u8 = 0x05;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_USER_PASS: u8 = 0x02;
+const METHOD_NO_ACCEPTABLE: u8 = 0xff;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const USER_PASS_VERSION: u8 = 0x01;
+
+/// Performs the SOCKS5 handshake against an already-connected `stream`, establishing a tunnel to
+/// `target_host`/`target_port` through it.
+pub(crate) async fn connect<S>(
+    stream: &mut S,
+    proxy: &SocksConfig,
+    target_host: &str,
+    target_port: u16,
+) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    negotiate_method(stream, proxy).await?;
+
+    if proxy.username.is_some() || proxy.password.is_some() {
+        authenticate(stream, proxy).await?;
+    }
+
+    request_connect(stream, target_host, target_port).await
+}
+
+async fn negotiate_method<S>(stream: &mut S, proxy: &SocksConfig) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let wants_auth = proxy.username.is_some() || proxy.password.is_some();
+    let methods: &[u8] = if wants_auth {
+        &[METHOD_NO_AUTH, METHOD_USER_PASS]
+    } else {
+        &[METHOD_NO_AUTH]
+    };
+
+    let mut request = Vec::with_capacity(2 + methods.len());
+    request.push(VERSION);
+    request.push(methods.len() as u8);
+    request.extend_from_slice(methods);
+    stream.write_all(&request).await?;
+
+    let mut response = [0u8; 2];
+    stream.read_exact(&mut response).await?;
+    if response[0] != VERSION {
+        return Err(Error::connect("SOCKS5 proxy returned an unexpected version".to_string()));
+    }
+    match response[1] {
+        METHOD_NO_AUTH | METHOD_USER_PASS => Ok(()),
+        METHOD_NO_ACCEPTABLE => {
+            Err(Error::connect("SOCKS5 proxy rejected all authentication methods".to_string()))
+        }
+        other => Err(Error::connect(format!(
+            "SOCKS5 proxy selected unsupported method {}",
+            other
+        ))),
+    }
+}
+
+async fn authenticate<S>(stream: &mut S, proxy: &SocksConfig) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let username = proxy.username.as_deref().unwrap_or("");
+    let password = proxy.password.as_deref().unwrap_or("");
+
+    if username.len() > 255 || password.len() > 255 {
+        return Err(Error::connect(
+            "SOCKS5 username/password must each be at most 255 bytes".to_string(),
+        ));
+    }
+
+    let mut request = Vec::with_capacity(3 + username.len() + password.len());
+    request.push(USER_PASS_VERSION);
+    request.push(username.len() as u8);
+    request.extend_from_slice(username.as_bytes());
+    request.push(password.len() as u8);
+    request.extend_from_slice(password.as_bytes());
+    stream.write_all(&request).await?;
+
+    let mut response = [0u8; 2];
+    stream.read_exact(&mut response).await?;
+    if response[1] != 0 {
+        return Err(Error::connect("SOCKS5 proxy authentication failed".to_string()));
+    }
+
+    Ok(())
+}
+
+async fn request_connect<S>(stream: &mut S, target_host: &str, target_port: u16) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    if target_host.len() > 255 {
+        return Err(Error::connect("SOCKS5 target hostname is too long".to_string()));
+    }
+
+    let mut request = Vec::with_capacity(7 + target_host.len());
+    request.push(VERSION);
+    request.push(CMD_CONNECT);
+    request.push(0x00); // reserved
+    request.push(ATYP_DOMAIN);
+    request.push(target_host.len() as u8);
+    request.extend_from_slice(target_host.as_bytes());
+    request.extend_from_slice(&target_port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[0] != VERSION {
+        return Err(Error::connect("SOCKS5 proxy returned an unexpected version".to_string()));
+    }
+    if head[1] != 0x00 {
+        return Err(Error::connect(format!(
+            "SOCKS5 CONNECT failed with reply code {}",
+            head[1]
+        )));
+    }
+
+    // Consume and discard the bound address the proxy reports, sized by ATYP.
+    match head[3] {
+        0x01 => {
+            let mut buf = [0u8; 4 + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            let mut buf = vec![0u8; len[0] as usize + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        0x04 => {
+            let mut buf = [0u8; 16 + 2];
+            stream.read_exact(&mut buf).await?;
+        }
+        other => {
+            return Err(Error::connect(format!(
+                "SOCKS5 proxy returned unsupported address type {}",
+                other
+            )))
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! TLS support.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::config::SslMode;
+use crate::error::Error;
+
+/// A `TlsConnect` implementor that can be used when TLS is not required.
+#[derive(Debug, Copy, Clone)]
+pub struct NoTls;
+
+impl<S> TlsConnect<S> for NoTls
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Stream = S;
+    type Future = Pin<Box<dyn Future<Output = Result<S, Error>> + Send>>;
+
+    fn connect(self, stream: S, _ssl_mode: SslMode) -> Self::Future {
+        Box::pin(async move { Ok(stream) })
+    }
+
+    fn supported_by_config(&self) -> bool {
+        false
+    }
+}
+
+/// A trait for types that can upgrade a plaintext stream to one protected by TLS.
+pub trait TlsConnect<S> {
+    /// The stream type produced once the handshake completes.
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+    /// The future returned by `connect`.
+    type Future: Future<Output = Result<Self::Stream, Error>> + Send;
+
+    /// Begins the TLS handshake against `stream`.
+    ///
+    /// `ssl_mode` is the mode the connection was configured with; implementors that can verify
+    /// certificates should only do chain validation for `SslMode::VerifyCa` and additionally
+    /// check the server hostname for `SslMode::VerifyFull`, matching how `libpq` treats those
+    /// modes.
+    fn connect(self, stream: S, ssl_mode: SslMode) -> Self::Future;
+
+    /// Whether this connector is able to perform a real TLS handshake, as opposed to `NoTls`.
+    ///
+    /// `Config::ssl_mode` uses this to decide whether `SslMode::Prefer`/`SslMode::Require` can be
+    /// honored at all for a given connection attempt.
+    fn supported_by_config(&self) -> bool {
+        true
+    }
+}
+
+/// The stream type backing a connection once TLS negotiation (driven by `Config::ssl_mode`) has
+/// run: either the raw, unencrypted socket, or one wrapped by a [`TlsConnect`] implementor.
+pub enum MaybeTlsStream<S, T> {
+    /// An unencrypted stream.
+    Raw(S),
+    /// A stream protected by TLS.
+    Tls(T),
+}
+
+impl<S, T> AsyncRead for MaybeTlsStream<S, T>
+where
+    S: AsyncRead + Unpin,
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Raw(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<S, T> AsyncWrite for MaybeTlsStream<S, T>
+where
+    S: AsyncWrite + Unpin,
+    T: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTlsStream::Raw(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Raw(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Raw(s) => Pin::new(s).poll_shutdown(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::time::Duration;
-use tokio_opengauss::config::{Config, TargetSessionAttrs};
+use tokio_opengauss::config::{Config, SslMode, TargetSessionAttrs};
 
 fn check(s: &str, config: &Config) {
     assert_eq!(s.parse::<Config>().expect(s), *config, "`{}`", s);
@@ -118,3 +118,30 @@ fn url() {
             .dbname("dbname"),
     )
 }
+
+#[test]
+fn sslmode() {
+    check(
+        "sslmode=verify-full",
+        Config::new().ssl_mode(SslMode::VerifyFull),
+    );
+    check("sslmode=disable", Config::new().ssl_mode(SslMode::Disable));
+    check("sslmode=require", Config::new().ssl_mode(SslMode::Require));
+    check(
+        "sslmode=verify-ca",
+        Config::new().ssl_mode(SslMode::VerifyCa),
+    );
+}
+
+#[test]
+fn sslmode_invalid() {
+    "sslmode=bogus".parse::<Config>().unwrap_err();
+}
+
+#[test]
+fn socks_proxy() {
+    check(
+        "socks_proxy=proxy.example:1080 socks_username=alice socks_password=hunter2",
+        Config::new().socks_proxy("proxy.example", 1080).socks_username("alice").socks_password("hunter2"),
+    );
+}
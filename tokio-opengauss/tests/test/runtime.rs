@@ -17,7 +17,7 @@ async fn smoke_test(s: &str) {
 
     let stmt = client.prepare("SELECT $1::INT").await.unwrap();
     let rows = client.query(&stmt, &[&1i32]).await.unwrap();
-    assert_eq!(rows[0].get::<_, i32>(0), 1i32);
+    assert_eq!(rows[0].get::<i32>(0), 1i32);
 }
 
 #[tokio::test]